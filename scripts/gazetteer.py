@@ -0,0 +1,69 @@
+#!/usr/bin/env python3
+"""Builds src/geolocation/files/cities.tsv from a GeoNames dump.
+
+Download cities15000.txt (from cities15000.zip), admin1CodesASCII.txt and countryInfo.txt
+from https://download.geonames.org/export/dump/ and run:
+
+    scripts/gazetteer.py cities15000.txt admin1CodesASCII.txt countryInfo.txt > src/geolocation/files/cities.tsv
+"""
+
+import sys
+
+HEADER = """# Cities for offline geocoding with the `gazetteer` feature, derived from GeoNames
+# (https://www.geonames.org, CC BY 4.0). Regenerate a larger table with scripts/gazetteer.py.
+# name\talternate names\tadmin1\tcountry\tcountry code\tlatitude\tlongitude\ttimezone\tpopulation"""
+
+# Alternate names are limited to Latin-script spellings, which keeps the table compact.
+MAX_ALTERNATE_NAMES = 5
+
+
+def is_latin(name):
+    return all(ord(char) < 0x250 for char in name)
+
+
+def main(cities_path, admin1_path, countries_path):
+    admin1 = {}
+    with open(admin1_path, encoding="utf-8") as file:
+        for line in file:
+            code, name, *_ = line.rstrip("\n").split("\t")
+            admin1[code] = name
+
+    countries = {}
+    with open(countries_path, encoding="utf-8") as file:
+        for line in file:
+            if line.startswith("#"):
+                continue
+            fields = line.rstrip("\n").split("\t")
+            countries[fields[0]] = fields[4]
+
+    print(HEADER)
+    with open(cities_path, encoding="utf-8") as file:
+        for line in file:
+            fields = line.rstrip("\n").split("\t")
+            name, alternates = fields[1], fields[3].split(",")
+            country_code, admin1_code = fields[8], fields[10]
+            alternates = [
+                alternate
+                for alternate in dict.fromkeys(alternates)
+                if alternate and alternate != name and is_latin(alternate)
+            ][:MAX_ALTERNATE_NAMES]
+
+            print(
+                "\t".join(
+                    [
+                        name,
+                        ",".join(alternates),
+                        admin1.get(f"{country_code}.{admin1_code}", ""),
+                        countries.get(country_code, ""),
+                        country_code,
+                        fields[4],
+                        fields[5],
+                        fields[17],
+                        fields[14],
+                    ]
+                )
+            )
+
+
+if __name__ == "__main__":
+    main(*sys.argv[1:])
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// An on-disk store of API responses, keyed by the request URL.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// A struct representing a stored response using the `url`, its `body` and when it was `fetched_at`.
+#[derive(Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub fetched_at: u64,
+    pub body: String,
+}
+
+impl CacheEntry {
+    /// Returns how long ago the response was fetched.
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+}
+
+impl Cache {
+    /// Instantiate a new `Cache` storing its entries in `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Cache { dir }
+    }
+
+    /// Returns the default cache directory, e.g. `$XDG_CACHE_HOME/weather-wand` on Linux.
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("weather-wand"))
+    }
+
+    /// Returns the stored response for `url`, regardless of its age.
+    pub fn get(&self, url: &str) -> Option<CacheEntry> {
+        let content = fs::read_to_string(self.path_for(url)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+
+        // Guard against hash collisions between different URLs.
+        (entry.url == url).then_some(entry)
+    }
+
+    /// Returns the stored response for `url` if it is younger than `ttl`.
+    pub fn get_fresh(&self, url: &str, ttl: Duration) -> Option<CacheEntry> {
+        self.get(url).filter(|entry| entry.age() < ttl)
+    }
+
+    /// Stores the response `body` fetched from `url`, replacing an older entry.
+    ///
+    /// # Errors
+    /// * The cache directory cannot be created.
+    /// * The entry cannot be written.
+    pub fn put(&self, url: &str, body: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let entry = CacheEntry {
+            url: url.to_string(),
+            fetched_at: now(),
+            body: body.to_string(),
+        };
+
+        // Write to a temporary file first, so concurrent readers never see a partial entry.
+        // Its name is unique, so concurrent writers of the same URL don't write into one file.
+        let path = self.path_for(url);
+        let temporary_path = path.with_extension(format!(
+            "{}.{:08x}.tmp",
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        fs::write(&temporary_path, serde_json::to_string(&entry)?)?;
+        fs::rename(&temporary_path, path).map_err(|err| {
+            let _ = fs::remove_file(&temporary_path);
+            err
+        })
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", fnv1a(url)))
+    }
+}
+
+/// A stable 64-bit FNV-1a hash used to derive file names from URLs.
+fn fnv1a(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fnv1a, Cache};
+    use std::time::Duration;
+
+    #[test]
+    fn put_and_get_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().join("weather-wand"));
+
+        cache.put("https://example.com/a", r#"{"a":1}"#).unwrap();
+
+        let entry = cache.get("https://example.com/a").unwrap();
+        assert_eq!(entry.body, r#"{"a":1}"#);
+        assert!(entry.age() < Duration::from_secs(5));
+        assert!(cache.get("https://example.com/b").is_none());
+    }
+
+    #[test]
+    fn get_fresh_respects_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().to_path_buf());
+
+        cache.put("https://example.com/a", "{}").unwrap();
+
+        assert!(cache
+            .get_fresh("https://example.com/a", Duration::from_secs(60))
+            .is_some());
+        assert!(cache
+            .get_fresh("https://example.com/a", Duration::ZERO)
+            .is_none());
+    }
+
+    #[test]
+    fn concurrent_puts_of_same_url_all_succeed() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().to_path_buf());
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let cache = &cache;
+                scope.spawn(move || {
+                    cache
+                        .put("https://example.com/a", &format!("{{\"i\":{}}}", i))
+                        .unwrap()
+                });
+            }
+        });
+
+        assert!(cache.get("https://example.com/a").is_some());
+        let files = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(files, 1, "temporary files are left behind");
+    }
+
+    #[test]
+    fn fnv1a_is_stable() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,823 @@
+pub mod query;
+pub mod resolver;
+
+use crate::cache::{Cache, CacheEntry};
+use crate::config::args::Args;
+use crate::config::Config;
+use crate::errors::CustomError;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use resolver::Resolver;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A struct representing the settings used to build the shared HTTP `Client`.
+pub struct ClientSettings {
+    pub timeout: Duration,
+    pub user_agent: String,
+    pub proxy: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub retry: RetryPolicy,
+    pub cache_dir: Option<PathBuf>,
+    pub refresh: bool,
+    pub offline: bool,
+}
+
+/// A struct representing how failed requests are retried using exponential backoff with jitter.
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base_delay: Duration,
+    pub max_wait: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait before retry number `attempt` (starting at 0).
+    ///
+    /// A `Retry-After` given by the server is honored, otherwise the delay doubles with every
+    /// attempt and a random jitter of up to half the delay is subtracted. The delay never exceeds `max_wait`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let delay = match retry_after {
+            Some(retry_after) => retry_after,
+            None => {
+                let backoff = self
+                    .base_delay
+                    .saturating_mul(2u32.saturating_pow(attempt))
+                    .min(self.max_wait);
+                let jitter = rand::thread_rng().gen_range(0.0..=0.5);
+
+                backoff.mul_f64(1.0 - jitter)
+            }
+        };
+
+        delay.min(self.max_wait)
+    }
+}
+
+impl ClientSettings {
+    /// Creates the settings from the defaults in config.json.
+    ///
+    /// # Errors
+    /// * A value is missing in config.json.
+    /// * A configured timeout, delay or retry count is not a number.
+    /// * A configured header is not given as `Name: value`.
+    pub fn from_config() -> Result<Self, CustomError> {
+        let timeout = Config::get_number("http_timeout_seconds")?;
+        let proxy = Config::get_value("http_proxy")?;
+
+        Ok(ClientSettings {
+            timeout: Duration::from_secs(timeout),
+            user_agent: Config::get_value("http_user_agent")?,
+            proxy: (!proxy.is_empty()).then_some(proxy),
+            headers: parse_headers(&Config::get_value("http_headers")?)
+                .map_err(|err| CustomError::Config(format!("http_headers: {}", err)))?,
+            retry: RetryPolicy {
+                retries: Config::get_number("http_retries")? as u32,
+                base_delay: Duration::from_millis(Config::get_number("http_retry_base_delay_ms")?),
+                max_wait: Duration::from_secs(Config::get_number("http_retry_max_wait_seconds")?),
+            },
+            cache_dir: Cache::default_dir(),
+            refresh: false,
+            offline: false,
+        })
+    }
+
+    /// Overrides the settings with the values given on the command line.
+    pub fn with_args(mut self, args: &Args) -> Self {
+        if let Some(timeout) = args.timeout {
+            self.timeout = Duration::from_secs(timeout);
+        }
+        if let Some(user_agent) = &args.user_agent {
+            self.user_agent = user_agent.clone();
+        }
+        if let Some(proxy) = &args.proxy {
+            self.proxy = Some(proxy.clone());
+        }
+        self.headers.extend(args.header.iter().cloned());
+        if let Some(retries) = args.retries {
+            self.retry.retries = retries;
+        }
+        if let Some(retry_max_wait) = args.retry_max_wait {
+            self.retry.max_wait = Duration::from_secs(retry_max_wait);
+        }
+        if args.no_cache {
+            self.cache_dir = None;
+        }
+        self.refresh = args.refresh;
+        self.offline = args.offline;
+
+        self
+    }
+}
+
+/// The HTTP client shared by the geolocation and weather APIs.
+/// It is built once so all requests share the same connection pool.
+pub struct Client {
+    inner: reqwest::Client,
+    retry: RetryPolicy,
+    cache: Option<Cache>,
+    refresh: bool,
+    offline: bool,
+}
+
+/// A struct representing a response `body` returned by `Client::fetch_cached`.
+/// `stale_age` is set if the body is an outdated stored response, served because the API
+/// could not be reached.
+pub struct Fetched {
+    pub body: String,
+    pub stale_age: Option<Duration>,
+}
+
+impl Fetched {
+    fn fresh(body: String) -> Self {
+        Fetched {
+            body,
+            stale_age: None,
+        }
+    }
+
+    fn stored(entry: CacheEntry) -> Self {
+        Fetched {
+            stale_age: Some(entry.age()),
+            body: entry.body,
+        }
+    }
+}
+
+/// Returns whether the error means the API could not be reached at all,
+/// in which case a stored response is better than nothing.
+fn is_unreachable(err: &CustomError) -> bool {
+    match err {
+        CustomError::Network { .. } | CustomError::Timeout(_) => true,
+        CustomError::HttpStatus { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+impl Client {
+    /// Instantiate a new `Client` with the given settings.
+    ///
+    /// # Errors
+    /// * The proxy URL or one of the headers is invalid.
+    /// * The underlying HTTP client cannot be built.
+    pub fn new(settings: ClientSettings) -> Result<Self, CustomError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &settings.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|err| CustomError::Config(format!("header '{}': {}", name, err)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|err| CustomError::Config(format!("header '{}': {}", name, err)))?;
+            headers.append(name, value);
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(settings.timeout)
+            .user_agent(&settings.user_agent)
+            .default_headers(headers)
+            .dns_resolver(Arc::new(Resolver));
+
+        if let Some(proxy) = &settings.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|err| CustomError::Config(format!("proxy '{}': {}", proxy, err)))?;
+            builder = builder.proxy(proxy);
+        }
+
+        Ok(Client {
+            inner: builder.build()?,
+            retry: settings.retry,
+            cache: settings.cache_dir.map(Cache::new),
+            refresh: settings.refresh,
+            offline: settings.offline,
+        })
+    }
+
+    /// Fetches data like `fetch`, but answers from the response cache if the stored
+    /// response for `url` is younger than `ttl`. Fresh responses are written to the cache.
+    ///
+    /// If the API cannot be reached, or the client is offline, the last stored response is
+    /// returned regardless of its age and marked with `stale_age`.
+    ///
+    /// # Arguments
+    /// * `url`: The URL from which to fetch the data.
+    /// * `ttl`: How long a cached response for this URL stays valid.
+    ///
+    /// # Errors
+    /// * The same errors as `fetch`, if no stored response can be used instead.
+    /// * `CustomError::NotStored` if the client is offline and nothing is stored for `url`.
+    ///
+    /// Failing to write the cache is not an error.
+    pub async fn fetch_cached(&self, url: &str, ttl: Duration) -> Result<Fetched, CustomError> {
+        if let Some(cache) = &self.cache {
+            if self.offline {
+                return cache.get(url).map(Fetched::stored).ok_or_else(|| {
+                    CustomError::NotStored(format!("no stored response for {}", url))
+                });
+            }
+
+            if !self.refresh {
+                if let Some(entry) = cache.get_fresh(url, ttl) {
+                    return Ok(Fetched::fresh(entry.body));
+                }
+            }
+        }
+
+        let body = match self.fetch(url).await {
+            Ok(body) => body,
+            Err(err) => {
+                let stored = self
+                    .cache
+                    .as_ref()
+                    .filter(|_| is_unreachable(&err))
+                    .and_then(|cache| cache.get(url));
+
+                return stored.map(Fetched::stored).ok_or(err);
+            }
+        };
+
+        if let Some(cache) = &self.cache {
+            // The cache is only an optimization, so a read-only or full disk must not fail the request.
+            let _ = cache.put(url, &body);
+        }
+
+        Ok(Fetched::fresh(body))
+    }
+
+    /// Fetches data from the specified URL using an asynchronous HTTP request.
+    /// Transient failures are retried according to the `RetryPolicy` of the client.
+    ///
+    /// # Arguments
+    /// * `url`: The URL from which to fetch the data.
+    ///
+    /// # Returns
+    /// Returns a `Result` containing a string with the fetched data if successful,
+    /// or an error if the request fails, the timeout is exceeded, the URL is invalid
+    /// or the API responds with a non-success status.
+    ///
+    /// # Errors
+    /// * `CustomError::Network` with the underlying cause if the request cannot be sent.
+    /// * `CustomError::Timeout` if the API does not respond in time.
+    /// * `CustomError::HttpStatus` if the API responds with a non-success status.
+    pub async fn fetch(&self, url: &str) -> Result<String, CustomError> {
+        let mut attempt = 0;
+
+        loop {
+            match self.fetch_once(url).await {
+                Ok(body) => return Ok(body),
+                Err((err, retry_after)) if err.is_retryable() && attempt < self.retry.retries => {
+                    tokio::time::sleep(self.retry.delay(attempt, retry_after)).await;
+                    attempt += 1;
+                }
+                Err((err, _)) => return Err(err),
+            }
+        }
+    }
+
+    /// Sends a single request and returns the error together with the server's `Retry-After`, if any.
+    async fn fetch_once(&self, url: &str) -> Result<String, (CustomError, Option<Duration>)> {
+        let response = self
+            .inner
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| (err.into(), None))?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().await.map_err(|err| (err.into(), None))?;
+
+        if !status.is_success() {
+            let err = CustomError::HttpStatus {
+                status: status.as_u16(),
+                reason: error_reason(&body)
+                    .unwrap_or_else(|| status.canonical_reason().unwrap_or_default().to_string()),
+            };
+            return Err((err, retry_after));
+        }
+
+        Ok(body)
+    }
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+
+    // Dates in the past mean the request may be retried right away.
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Extracts the `reason` field Open-Meteo puts into the body of error responses.
+fn error_reason(body: &str) -> Option<String> {
+    let parsed: serde_json::Value = serde_json::from_str(body).ok()?;
+
+    parsed["reason"].as_str().map(|reason| reason.to_string())
+}
+
+/// Parses a `Name: value` header given on the command line or in the configuration.
+pub fn parse_header(header: &str) -> Result<(String, String), String> {
+    let (name, value) = header
+        .split_once(':')
+        .ok_or_else(|| format!("expected 'Name: value', got '{}'", header))?;
+
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+/// Parses the configured `http_headers`, one `Name: value` header per line.
+pub fn parse_headers(headers: &str) -> Result<Vec<(String, String)>, String> {
+    headers
+        .lines()
+        .filter(|header| !header.trim().is_empty())
+        .map(parse_header)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_header, parse_headers, parse_retry_after, Client, ClientSettings, RetryPolicy,
+    };
+    use crate::errors::{CustomError, NetworkCause};
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn default_client() -> Client {
+        Client::new(ClientSettings::from_config().unwrap()).unwrap()
+    }
+
+    fn client_with_retries(retries: u32, max_wait: Duration) -> Client {
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.retry = RetryPolicy {
+            retries,
+            base_delay: Duration::from_millis(10),
+            max_wait,
+        };
+
+        Client::new(settings).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetch_returns_correct_response() {
+        let server = MockServer::start();
+
+        let expected_json = json!({
+            "current_weather": {
+                "time": 0,
+                "interval": 0,
+                "temperature": 10.5,
+                "windspeed": 12.8,
+            },
+        });
+
+        let weather_mock = server.mock(|when, then| {
+            when.method(GET).path("/weather");
+            then.status(200).json_body(expected_json.clone());
+        });
+
+        let result = default_client().fetch(&server.url("/weather")).await;
+
+        let actual_json: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+
+        weather_mock.assert();
+        assert_eq!(actual_json, expected_json);
+    }
+
+    #[tokio::test]
+    async fn exceeding_timeout_triggers_error() {
+        let start_time = SystemTime::now();
+        let two_seconds = Duration::from_secs(2);
+
+        let server = MockServer::start();
+
+        let timeout_mock = server.mock(|when, then| {
+            when.method(GET).path("/timeout");
+            then.status(200).delay(two_seconds);
+        });
+
+        let _response = client_with_retries(0, Duration::from_secs(1))
+            .fetch(&server.url("/timeout"))
+            .await;
+
+        timeout_mock.assert();
+        assert!(start_time.elapsed().unwrap() > two_seconds);
+        assert!(matches!(_response, Err(CustomError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn configured_timeout_is_used() {
+        let server = MockServer::start();
+
+        let timeout_mock = server.mock(|when, then| {
+            when.method(GET).path("/slow");
+            then.status(200).delay(Duration::from_secs(3));
+        });
+
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.timeout = Duration::from_secs(5);
+
+        let result = Client::new(settings)
+            .unwrap()
+            .fetch(&server.url("/slow"))
+            .await;
+
+        timeout_mock.assert();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn user_agent_and_extra_headers_are_sent() {
+        let server = MockServer::start();
+
+        let header_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/headers")
+                .header("user-agent", "weather-wand-test")
+                .header("x-team", "field-ops");
+            then.status(200).body("{}");
+        });
+
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.user_agent = "weather-wand-test".to_string();
+        settings.headers = vec![("X-Team".to_string(), "field-ops".to_string())];
+
+        let result = Client::new(settings)
+            .unwrap()
+            .fetch(&server.url("/headers"))
+            .await;
+
+        header_mock.assert();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn headers_from_config_are_sent() {
+        let server = MockServer::start();
+
+        let header_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/headers")
+                .header("x-team", "field-ops")
+                .header("accept-language", "de, en;q=0.5");
+            then.status(200).body("{}");
+        });
+
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.headers =
+            parse_headers("X-Team: field-ops\nAccept-Language: de, en;q=0.5\n").unwrap();
+
+        let result = Client::new(settings)
+            .unwrap()
+            .fetch(&server.url("/headers"))
+            .await;
+
+        header_mock.assert();
+        assert!(result.is_ok());
+        assert!(parse_headers("X-Team field-ops").is_err());
+    }
+
+    #[tokio::test]
+    async fn error_status_returns_http_status_error_with_reason() {
+        let server = MockServer::start();
+
+        let error_mock = server.mock(|when, then| {
+            when.method(GET).path("/error");
+            then.status(400)
+                .json_body(json!({"error": true, "reason": "Invalid value"}));
+        });
+
+        let result = default_client().fetch(&server.url("/error")).await;
+
+        error_mock.assert();
+        match result {
+            Err(CustomError::HttpStatus { status, reason }) => {
+                assert_eq!(status, 400);
+                assert_eq!(reason, "Invalid value");
+            }
+            _ => panic!("expected an HttpStatus error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn refused_connection_returns_network_error() {
+        // Bind to a free port and release it again, so nothing is listening there.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let result = client_with_retries(0, Duration::from_secs(1))
+            .fetch(&format!("http://127.0.0.1:{}/", port))
+            .await;
+
+        match result {
+            Err(CustomError::Network { cause, .. }) => {
+                assert_eq!(cause, NetworkCause::ConnectionRefused)
+            }
+            _ => panic!("expected a Network error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_host_returns_dns_error() {
+        // The .invalid top-level domain is reserved and never resolves.
+        let result = default_client().fetch("http://weather-wand.invalid/").await;
+
+        match result {
+            Err(CustomError::Network { cause, .. }) => assert_eq!(cause, NetworkCause::Dns),
+            _ => panic!("expected a Network error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_handshake_returns_tls_error() {
+        // The mock server only speaks plain HTTP, so the TLS handshake fails.
+        let server = MockServer::start();
+
+        let result = default_client()
+            .fetch(&format!("https://{}/", server.address()))
+            .await;
+
+        match result {
+            Err(CustomError::Network { cause, .. }) => assert_eq!(cause, NetworkCause::Tls),
+            _ => panic!("expected a Network error"),
+        }
+    }
+
+    #[test]
+    fn invalid_proxy_returns_config_error() {
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.proxy = Some("not a proxy url".to_string());
+
+        assert!(matches!(Client::new(settings), Err(CustomError::Config(_))));
+    }
+
+    #[test]
+    fn parse_header_splits_name_and_value() {
+        assert_eq!(
+            parse_header("X-Api-Key: abc:123").unwrap(),
+            ("X-Api-Key".to_string(), "abc:123".to_string())
+        );
+        assert!(parse_header("no separator").is_err());
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        let server = MockServer::start();
+
+        let unavailable_mock = server.mock(|when, then| {
+            when.method(GET).path("/unavailable");
+            then.status(503);
+        });
+
+        let result = client_with_retries(2, Duration::from_secs(1))
+            .fetch(&server.url("/unavailable"))
+            .await;
+
+        unavailable_mock.assert_hits(3);
+        assert!(matches!(
+            result,
+            Err(CustomError::HttpStatus { status: 503, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn client_errors_fail_immediately() {
+        let server = MockServer::start();
+
+        let not_found_mock = server.mock(|when, then| {
+            when.method(GET).path("/not-found");
+            then.status(404);
+        });
+
+        let result = client_with_retries(2, Duration::from_secs(1))
+            .fetch(&server.url("/not-found"))
+            .await;
+
+        not_found_mock.assert_hits(1);
+        assert!(matches!(
+            result,
+            Err(CustomError::HttpStatus { status: 404, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn retry_after_is_honored_up_to_max_wait() {
+        let server = MockServer::start();
+
+        let rate_limit_mock = server.mock(|when, then| {
+            when.method(GET).path("/rate-limited");
+            then.status(429).header("Retry-After", "1");
+        });
+
+        let start_time = SystemTime::now();
+        let _ = client_with_retries(1, Duration::from_secs(5))
+            .fetch(&server.url("/rate-limited"))
+            .await;
+
+        rate_limit_mock.assert_hits(2);
+        assert!(start_time.elapsed().unwrap() >= Duration::from_secs(1));
+
+        let start_time = SystemTime::now();
+        let _ = client_with_retries(1, Duration::from_millis(100))
+            .fetch(&server.url("/rate-limited"))
+            .await;
+
+        assert!(start_time.elapsed().unwrap() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn delay_grows_exponentially_with_jitter_and_is_capped() {
+        let policy = RetryPolicy {
+            retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_wait: Duration::from_millis(500),
+        };
+
+        for (attempt, full_delay) in [(0, 100), (1, 200), (2, 400), (3, 500), (4, 500)] {
+            let delay = policy.delay(attempt, None);
+            assert!(delay <= Duration::from_millis(full_delay));
+            assert!(delay >= Duration::from_millis(full_delay / 2));
+        }
+
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_millis(300))),
+            Duration::from_millis(300)
+        );
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(60))),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn parse_retry_after_accepts_seconds_and_dates() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert!(parse_retry_after("soon").is_none());
+    }
+
+    fn cached_client(cache_dir: &Path, refresh: bool) -> Client {
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.cache_dir = Some(cache_dir.to_path_buf());
+        settings.refresh = refresh;
+        settings.retry.retries = 0;
+
+        Client::new(settings).unwrap()
+    }
+
+    fn offline_client(cache_dir: &Path) -> Client {
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.cache_dir = Some(cache_dir.to_path_buf());
+        settings.offline = true;
+
+        Client::new(settings).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetch_cached_answers_from_cache_within_ttl() {
+        let server = MockServer::start();
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let weather_mock = server.mock(|when, then| {
+            when.method(GET).path("/cached");
+            then.status(200).body(r#"{"cached":true}"#);
+        });
+
+        let client = cached_client(cache_dir.path(), false);
+        let ttl = Duration::from_secs(60);
+        let first = client.fetch_cached(&server.url("/cached"), ttl).await;
+        let second = client.fetch_cached(&server.url("/cached"), ttl).await;
+
+        weather_mock.assert_hits(1);
+        assert_eq!(first.unwrap().body, second.unwrap().body);
+
+        let _ = client
+            .fetch_cached(&server.url("/cached"), Duration::ZERO)
+            .await;
+
+        weather_mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn fetch_cached_refresh_skips_cache_but_updates_it() {
+        let server = MockServer::start();
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let weather_mock = server.mock(|when, then| {
+            when.method(GET).path("/refresh");
+            then.status(200).body("{}");
+        });
+
+        let ttl = Duration::from_secs(60);
+        let refreshing_client = cached_client(cache_dir.path(), true);
+        let _ = refreshing_client
+            .fetch_cached(&server.url("/refresh"), ttl)
+            .await;
+        let _ = refreshing_client
+            .fetch_cached(&server.url("/refresh"), ttl)
+            .await;
+
+        weather_mock.assert_hits(2);
+
+        let _ = cached_client(cache_dir.path(), false)
+            .fetch_cached(&server.url("/refresh"), ttl)
+            .await;
+
+        weather_mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn failed_responses_are_not_cached() {
+        let server = MockServer::start();
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let error_mock = server.mock(|when, then| {
+            when.method(GET).path("/failing");
+            then.status(404);
+        });
+
+        let client = cached_client(cache_dir.path(), false);
+        let ttl = Duration::from_secs(60);
+        let _ = client.fetch_cached(&server.url("/failing"), ttl).await;
+        let _ = client.fetch_cached(&server.url("/failing"), ttl).await;
+
+        error_mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn offline_serves_stored_response_without_request() {
+        let server = MockServer::start();
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let weather_mock = server.mock(|when, then| {
+            when.method(GET).path("/offline");
+            then.status(200).body(r#"{"stored":true}"#);
+        });
+
+        let _ = cached_client(cache_dir.path(), false)
+            .fetch_cached(&server.url("/offline"), Duration::from_secs(60))
+            .await;
+
+        let result = offline_client(cache_dir.path())
+            .fetch_cached(&server.url("/offline"), Duration::ZERO)
+            .await
+            .unwrap();
+
+        weather_mock.assert_hits(1);
+        assert_eq!(result.body, r#"{"stored":true}"#);
+        assert!(result.stale_age.is_some());
+    }
+
+    #[tokio::test]
+    async fn offline_without_stored_response_returns_not_stored_error() {
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let result = offline_client(cache_dir.path())
+            .fetch_cached("http://127.0.0.1:1/nothing", Duration::ZERO)
+            .await;
+
+        assert!(matches!(result, Err(CustomError::NotStored(_))));
+    }
+
+    #[tokio::test]
+    async fn unreachable_api_falls_back_to_stored_response() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start();
+
+        let unavailable_mock = server.mock(|when, then| {
+            when.method(GET).path("/fallback");
+            then.status(503);
+        });
+
+        let url = server.url("/fallback");
+        crate::cache::Cache::new(cache_dir.path().to_path_buf())
+            .put(&url, r#"{"stored":true}"#)
+            .unwrap();
+
+        let result = cached_client(cache_dir.path(), true)
+            .fetch_cached(&url, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        unavailable_mock.assert_hits(1);
+        assert_eq!(result.body, r#"{"stored":true}"#);
+        assert!(result.stale_age.is_some());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::errors::CustomError;
+use reqwest::Url;
+
+/// Parses the configured URL of an API, e.g. the `geo_api_url`.
+///
+/// # Errors
+/// * The URL is not a valid absolute URL.
+pub fn parse_url(url: &str) -> Result<Url, CustomError> {
+    Url::parse(url).map_err(|err| CustomError::Config(format!("invalid URL '{}': {}", url, err)))
+}
+
+/// Sets the query parameter `name` of `url` to `value`, keeping its position if it is already
+/// present. The value is percent-encoded, so it cannot break out of the parameter.
+pub fn set_param(url: &mut Url, name: &str, value: &str) {
+    let mut found = false;
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter_map(|(key, current)| {
+            if key != name {
+                return Some((key.into_owned(), current.into_owned()));
+            }
+            if found {
+                return None;
+            }
+            found = true;
+            Some((key.into_owned(), value.to_string()))
+        })
+        .collect();
+
+    let mut query = url.query_pairs_mut();
+    query.clear().extend_pairs(pairs);
+    if !found {
+        query.append_pair(name, value);
+    }
+}
+
+/// Adds `values` to the comma separated list in the query parameter `name` of `url`,
+/// e.g. extra variables to `hourly=temperature_2m,cloudcover`. Values already in the list are skipped.
+pub fn extend_list_param(url: &mut Url, name: &str, values: &[&str]) {
+    let current = url
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_default();
+
+    let mut list: Vec<&str> = current.split(',').filter(|item| !item.is_empty()).collect();
+    for value in values {
+        if !list.contains(value) {
+            list.push(value);
+        }
+    }
+
+    set_param(url, name, &list.join(","));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extend_list_param, parse_url, set_param};
+
+    #[test]
+    fn set_param_encodes_reserved_and_non_ascii_characters() {
+        let mut url = parse_url("https://example.com/search?name=Berlin&count=1").unwrap();
+
+        set_param(&mut url, "name", "Bar & Grill, TX #1+2");
+        set_param(&mut url, "name", "Saint-Étienne");
+
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/search?name=Saint-%C3%89tienne&count=1"
+        );
+
+        set_param(&mut url, "name", "Bar & Grill, TX #1+2");
+        let pairs: Vec<_> = url.query_pairs().collect();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].1, "Bar & Grill, TX #1+2");
+    }
+
+    #[test]
+    fn set_param_appends_missing_parameter() {
+        let mut url = parse_url("https://example.com/search?name=Berlin").unwrap();
+
+        set_param(&mut url, "countryCode", "DE");
+
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/search?name=Berlin&countryCode=DE"
+        );
+    }
+
+    #[test]
+    fn extend_list_param_adds_new_values_once() {
+        let mut url = parse_url("https://example.com/forecast?hourly=temperature_2m").unwrap();
+
+        extend_list_param(&mut url, "hourly", &["cloudcover", "temperature_2m"]);
+        extend_list_param(&mut url, "daily", &["sunrise"]);
+
+        let pairs: Vec<_> = url.query_pairs().collect();
+        assert_eq!(pairs[0].1, "temperature_2m,cloudcover");
+        assert_eq!(pairs[1].1, "sunrise");
+    }
+
+    #[test]
+    fn parse_url_rejects_relative_urls() {
+        assert!(parse_url("/v1/search?name=Berlin").is_err());
+    }
+}
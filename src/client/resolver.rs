@@ -0,0 +1,33 @@
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Resolves host names with the system resolver like reqwest's default one, but wraps
+/// failures in a `LookupError`, so they can be told apart from other connection errors.
+pub struct Resolver;
+
+impl Resolve for Resolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str().to_string(), 0))
+                .await
+                .map_err(LookupError)?;
+
+            Ok(Box::new(addrs) as Addrs)
+        })
+    }
+}
+
+/// The error of a failed host name lookup, see `Resolver`.
+#[derive(Debug)]
+pub struct LookupError(io::Error);
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for LookupError {}
@@ -1,5 +1,9 @@
-use clap::Parser;
+use crate::client::parse_header;
+use crate::geolocation::{parse_coordinates, parse_latitude, parse_longitude, Coordinates};
+use clap::{Parser, Subcommand};
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[clap(
@@ -7,31 +11,247 @@ use std::fmt::Display;
     about = "A CLI tool to fetch weather from an API and display a beautified output. Supports arguments for city, temperature- and wind speed unit."
 )]
 pub struct Args {
-    /// The city you want to see the current weather for.
-    /// | Example: --city "New York"
-    #[arg(short, long, required = true)]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// The city you want to see the current weather for, or the alias of a saved location.
+    /// Can be given multiple times. Defaults to the location saved as `default`,
+    /// then to the `city` of the configuration file.
+    /// | Example: --city "New York" --city Berlin
+    #[arg(short, long)]
     pub city: Vec<String>,
 
-    /// The unit used for displaying the temperature.
+    /// The latitude of the place to show the weather for, instead of a city. Requires --lon.
+    /// | Example: --lat 52.52 --lon 13.41
+    #[arg(long, requires = "lon", conflicts_with = "city", allow_negative_numbers = true, value_parser = parse_latitude)]
+    pub lat: Option<f64>,
+
+    /// The longitude of the place to show the weather for, instead of a city. Requires --lat.
+    /// | Example: --lat 52.52 --lon 13.41
+    #[arg(long, requires = "lat", allow_negative_numbers = true, value_parser = parse_longitude)]
+    pub lon: Option<f64>,
+
+    /// The coordinates of the place to show the weather for as LAT,LON, instead of a city.
+    /// | Example: --coords "52.52,13.41"
+    #[arg(long, conflicts_with_all = ["city", "lat"], allow_hyphen_values = true, value_parser = parse_coordinates)]
+    pub coords: Option<Coordinates>,
+
+    /// Only consider places in this country, given as name or ISO 3166-1 alpha-2 code.
+    /// Cities can also be qualified directly, e.g. "Portland, OR, US" or "Paris, FR".
+    /// | Example: --country FR
+    #[arg(long)]
+    pub country: Option<String>,
+
+    /// The unit used for displaying the temperature. [default: celsius]
     /// | Possible values: "celsius", "fahrenheit".
     /// | Example: --temperature-unit fahrenheit.
-    #[arg(short, long, default_value = "celsius")]
-    pub temperature_unit: TemperatureUnit,
+    #[arg(short, long)]
+    pub temperature_unit: Option<TemperatureUnit>,
 
-    /// The unit used for displaying the windspeed.
+    /// The unit used for displaying the windspeed. [default: kmh]
     /// | Possible values: "kmh", "ms", "mph", "kn".
     /// | Example: --windspeed-unit mph.
-    #[arg(short, long, default_value = "kmh")]
-    pub windspeed_unit: WindspeedUnit,
+    #[arg(short, long)]
+    pub windspeed_unit: Option<WindspeedUnit>,
 
-    /// The clock's display mode.
+    /// The clock's display mode. [default: 24h]
     /// | Possible values: "12h", "24h"
     /// | Example: --display 12h
-    #[arg(short, long, default_value = "24h")]
-    pub display: ClockDisplay,
+    #[arg(short, long)]
+    pub display: Option<ClockDisplay>,
+
+    /// The language of place names and output labels. [default: en]
+    /// | Possible values: "en", "de", "ja".
+    /// | Example: --lang de
+    #[arg(long, global = true)]
+    pub lang: Option<Language>,
+
+    /// The rows of the current weather report, in the order they are shown. Comma separated
+    /// or given multiple times. [default: temperature, feels-like, humidity, precipitation,
+    /// wind-speed, wind-direction, city, area, timezone, coordinates, elevation, update-time]
+    /// | Possible values: "temperature", "feels-like", "humidity", "pressure", "precipitation",
+    /// "cloud-cover", "visibility", "wind-speed", "wind-direction", "gusts", "uv-index", "city",
+    /// "area", "timezone", "coordinates", "elevation", "update-time".
+    /// | Example: --fields temperature,humidity,pressure
+    #[arg(long, value_delimiter = ',')]
+    pub fields: Vec<Field>,
+
+    /// How the weather of the cities is shown. [default: report]
+    /// | Possible values: "report", "compare".
+    /// | Example: --output compare
+    #[arg(short, long)]
+    pub output: Option<OutputFormat>,
+
+    /// A configuration file to use instead of the one in the user's config directory.
+    /// | Example: --config ~/weather-wand.toml
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Which place to use when a city name matches several places, as numbered in the list
+    /// of matches. Without it, the place is asked for on a terminal.
+    /// | Example: --city Frankfurt --pick 2
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..))]
+    pub pick: Option<u8>,
+
+    /// Show a daily forecast for the given number of days below the current weather.
+    /// | Possible values: 1 - 16.
+    /// | Example: --days 7
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=16))]
+    pub days: Option<u8>,
+
+    /// Show an hourly timeline for the given number of hours below the current weather.
+    /// | Possible values: 1 - 168.
+    /// | Example: --hourly 12
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=168))]
+    pub hourly: Option<u8>,
+
+    /// Show all cities side by side in a single comparison table instead of one report per city.
+    /// Shorthand for --output compare.
+    /// | Example: --compare --city Berlin --city Tokyo
+    #[arg(long, conflicts_with = "output")]
+    pub compare: bool,
+
+    /// Timeout in seconds for each request to the APIs.
+    /// | Example: --timeout 10
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// The User-Agent header sent with each request to the APIs.
+    /// | Example: --user-agent "my-dashboard/1.0"
+    #[arg(long)]
+    pub user_agent: Option<String>,
+
+    /// An HTTP(S) proxy used for all requests to the APIs.
+    /// | Example: --proxy http://proxy.example.com:3128
+    #[arg(long)]
+    pub proxy: Option<String>,
+
+    /// An extra header sent with each request to the APIs. Can be given multiple times.
+    /// | Example: --header "X-Api-Key: secret"
+    #[arg(long, value_parser = parse_header)]
+    pub header: Vec<(String, String)>,
+
+    /// How often a request is retried after a timeout, a reset or refused connection or a 5xx/429 response.
+    /// | Example: --retries 5
+    #[arg(long)]
+    pub retries: Option<u32>,
+
+    /// The longest time in seconds to wait before a retry, including a server's Retry-After.
+    /// | Example: --retry-max-wait 30
+    #[arg(long)]
+    pub retry_max_wait: Option<u64>,
+
+    /// Neither read nor write the on-disk response cache.
+    #[arg(long, conflicts_with = "refresh")]
+    pub no_cache: bool,
+
+    /// Ignore cached responses and fetch fresh data, updating the cache.
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Don't use the network and show the last stored weather, however old it is.
+    /// Stored data is also shown automatically if the API cannot be reached.
+    #[arg(long, conflicts_with_all = ["no_cache", "refresh"])]
+    pub offline: bool,
+
+    /// The base URL of the geocoding API, e.g. of a self-hosted Open-Meteo.
+    /// Replaces everything before the query of the configured URL.
+    /// | Example: --geo-api-url http://localhost:8080/v1/search
+    #[arg(long)]
+    pub geo_api_url: Option<String>,
+
+    /// The base URL of the reverse geocoding API naming coordinates, e.g. of a self-hosted Nominatim.
+    /// Replaces everything before the query of the configured URL.
+    /// | Example: --reverse-geo-api-url http://localhost:8080/reverse
+    #[arg(long)]
+    pub reverse_geo_api_url: Option<String>,
+
+    /// The base URL of the forecast API, e.g. of a self-hosted Open-Meteo.
+    /// Replaces everything before the query of the configured URLs.
+    /// | Example: --weather-api-url http://localhost:8080/v1/forecast
+    #[arg(long)]
+    pub weather_api_url: Option<String>,
 }
 
-#[derive(Clone, clap::ValueEnum)]
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manage locations saved under an alias, which can be used like a city with --city.
+    Locations {
+        #[command(subcommand)]
+        action: LocationsAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LocationsAction {
+    /// Save a place under an alias, replacing the place saved under the same alias.
+    /// The alias `default` is used when no city is given.
+    /// | Example: locations add office "Frankfurt am Main, Hesse"
+    Add {
+        /// The alias, e.g. `office`. Aliases are not case-sensitive.
+        alias: String,
+
+        /// The city to save, qualified like with --city.
+        #[arg(required_unless_present = "coords", conflicts_with = "coords")]
+        city: Option<String>,
+
+        /// The coordinates of the place to save as LAT,LON, instead of a city.
+        #[arg(long, allow_hyphen_values = true, value_parser = parse_coordinates)]
+        coords: Option<Coordinates>,
+
+        /// Only consider places in this country, given as name or ISO 3166-1 alpha-2 code.
+        #[arg(long)]
+        country: Option<String>,
+
+        /// Which place to save when the city name matches several places.
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..))]
+        pick: Option<u8>,
+    },
+
+    /// List the saved locations.
+    List,
+
+    /// Remove a saved location.
+    Remove {
+        /// The alias of the location to remove.
+        alias: String,
+    },
+
+    /// Save a location under another alias.
+    Rename {
+        /// The current alias of the location.
+        alias: String,
+
+        /// The new alias of the location.
+        new_alias: String,
+    },
+}
+
+impl Args {
+    /// Returns the coordinates given with --coords or --lat and --lon.
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        match (&self.coords, self.lat, self.lon) {
+            (Some(coordinates), _, _) => Some(*coordinates),
+            // Both are checked to be in range when the arguments are parsed.
+            (None, Some(latitude), Some(longitude)) => Coordinates::new(latitude, longitude).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the configuration values given as flags, keyed like config.json.
+    pub fn config_values(&self) -> HashMap<String, String> {
+        [
+            ("geo_api_base_url", &self.geo_api_url),
+            ("reverse_geo_api_base_url", &self.reverse_geo_api_url),
+            ("weather_api_base_url", &self.weather_api_url),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value.clone()?)))
+        .collect()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
 pub enum TemperatureUnit {
     Celsius,
     Fahrenheit,
@@ -47,7 +267,7 @@ impl Display for TemperatureUnit {
     }
 }
 
-#[derive(Clone, clap::ValueEnum)]
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
 pub enum WindspeedUnit {
     Kmh,
     Ms,
@@ -82,3 +302,59 @@ impl Display for ClockDisplay {
         write!(f, "{}", str)
     }
 }
+
+#[derive(Clone, PartialEq, Debug, clap::ValueEnum)]
+pub enum Language {
+    En,
+    De,
+    Ja,
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Language::En => "en".to_string(),
+            Language::De => "de".to_string(),
+            Language::Ja => "ja".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, clap::ValueEnum)]
+pub enum OutputFormat {
+    Report,
+    Compare,
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            OutputFormat::Report => "report".to_string(),
+            OutputFormat::Compare => "compare".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// A row of the current weather report.
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum Field {
+    Temperature,
+    FeelsLike,
+    Humidity,
+    Pressure,
+    Precipitation,
+    CloudCover,
+    Visibility,
+    WindSpeed,
+    WindDirection,
+    Gusts,
+    UvIndex,
+    City,
+    Area,
+    Timezone,
+    Coordinates,
+    Elevation,
+    UpdateTime,
+}
@@ -1,11 +1,37 @@
 pub mod args;
+pub mod settings;
 
+use crate::errors::CustomError;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The prefix of environment variables overriding configuration values,
+/// e.g. `WEATHER_WAND_TEMPERATURE_UNIT` for `temperature_unit`.
+const ENV_PREFIX: &str = "WEATHER_WAND_";
+
+/// The API URL templates and the key of the base URL replacing everything before their query.
+/// The parameters that vary, e.g. `latitude`, are set by name when a request is built.
+const API_URLS: [(&str, &str); 5] = [
+    ("geo_api_url", "geo_api_base_url"),
+    ("reverse_geo_api_url", "reverse_geo_api_base_url"),
+    ("weather_api_url", "weather_api_base_url"),
+    ("daily_weather_api_url", "weather_api_base_url"),
+    ("hourly_weather_api_url", "weather_api_base_url"),
+];
+
+/// Values from the user's configuration file, environment and flags, layered over config.json.
+static OVERRIDES: OnceLock<HashMap<String, String>> = OnceLock::new();
 
 pub struct Config;
 
 impl Config {
-    /// Gets a certain string value from config.json.
+    /// Gets a certain string value from the configuration.
+    ///
+    /// Values set in the environment take precedence over the user's configuration file,
+    /// which takes precedence over the compiled-in config.json.
     ///
     /// ## Arguments
     /// * `value`: The key to retrieve the corresponding value for.
@@ -18,41 +44,217 @@ impl Config {
     /// * The JSON file cannot be parsed.
     /// * The specified key is not found in the JSON structure.
     /// * The value associated with the key is not a string.
-    pub fn get_value(value: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let file_path = include_str!("files/config.json");
+    pub fn get_value(value: &str) -> Result<String, CustomError> {
+        if let Some(overridden) = OVERRIDES.get().and_then(|overrides| overrides.get(value)) {
+            return Ok(overridden.clone());
+        }
 
-        let parsed: Value = serde_json::from_str(file_path)?;
+        let parsed = Config::defaults()?;
 
         let json_value = parsed
             .get(value)
             .ok_or_else(|| {
-                Box::<dyn std::error::Error>::from(format!(
-                    "Invalid JSON format: value '{}' not found",
-                    value
-                ))
+                CustomError::Config(format!("Invalid JSON format: value '{}' not found", value))
             })?
             .as_str()
             .map(|s| s.to_string())
             .ok_or_else(|| {
-                Box::<dyn std::error::Error>::from(format!(
-                    "Value for key {} is not a string",
-                    value
-                ))
+                CustomError::Config(format!("Value for key {} is not a string", value))
             })?;
 
         Ok(json_value)
     }
+
+    /// Gets a certain value from the configuration and parses it as a number.
+    ///
+    /// ## Arguments
+    /// * `value`: The key to retrieve the corresponding value for.
+    ///
+    /// # Errors
+    /// * The value cannot be retrieved, see `get_value`.
+    /// * The value is not a non-negative integer.
+    pub fn get_number(value: &str) -> Result<u64, CustomError> {
+        Config::get_value(value)?
+            .parse::<u64>()
+            .map_err(|err| CustomError::Config(format!("{}: {}", value, err)))
+    }
+
+    /// Gets an API URL template from the configuration, with everything before its query
+    /// replaced by the configured base URL if one is set, e.g. for a self-hosted Open-Meteo.
+    ///
+    /// ## Arguments
+    /// * `value`: The key of the URL template, e.g. `geo_api_url`.
+    ///
+    /// # Errors
+    /// * The key is not an API URL template.
+    /// * The template or base URL cannot be retrieved, see `get_value`.
+    pub fn get_api_url(value: &str) -> Result<String, CustomError> {
+        let (_, base_key) = API_URLS
+            .iter()
+            .find(|(key, _)| *key == value)
+            .ok_or_else(|| CustomError::Config(format!("'{}' is not an API URL", value)))?;
+
+        Ok(with_base_url(
+            &Config::get_value(value)?,
+            &Config::get_value(base_key)?,
+        ))
+    }
+
+    /// Checks that all API URLs are valid HTTP(S) URLs without leftover placeholders,
+    /// so a broken override is reported at startup instead of on the first request.
+    ///
+    /// # Errors
+    /// * An API URL cannot be retrieved, is not an HTTP(S) URL or contains a placeholder.
+    pub fn validate_api_urls() -> Result<(), CustomError> {
+        for (key, _) in API_URLS {
+            check_api_url(key, &Config::get_api_url(key)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the user's configuration file, environment variables and the values given as
+    /// command-line flags, so that `get_value` returns them instead of the compiled-in defaults.
+    /// Must be called once at startup.
+    ///
+    /// ## Arguments
+    /// * `path`: An explicitly given configuration file. If `None`, the file in the
+    ///   user's config directory is used if it exists.
+    /// * `flags`: Configuration values given on the command line, see `Args::config_values`.
+    ///
+    /// # Errors
+    /// * The explicitly given file does not exist or cannot be read.
+    /// * The file is not valid TOML or contains unknown keys.
+    pub fn load(path: Option<&Path>, flags: HashMap<String, String>) -> Result<(), CustomError> {
+        let mut overrides = HashMap::new();
+
+        let file = match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => Config::default_path().filter(|path| path.exists()),
+        };
+
+        if let Some(file) = file {
+            let content = fs::read_to_string(&file).map_err(|err| {
+                CustomError::Config(format!("cannot read {}: {}", file.display(), err))
+            })?;
+            overrides.extend(
+                Config::parse_file(&content)
+                    .map_err(|err| CustomError::Config(format!("{}: {}", file.display(), err)))?,
+            );
+        }
+
+        overrides.extend(Config::parse_env(std::env::vars())?);
+        overrides.extend(flags);
+
+        let _ = OVERRIDES.set(overrides);
+
+        Ok(())
+    }
+
+    /// Returns the path of the user's configuration file, e.g. `~/.config/weather-wand/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("weather-wand").join("config.toml"))
+    }
+
+    fn defaults() -> Result<Value, CustomError> {
+        let file_path = include_str!("files/config.json");
+
+        serde_json::from_str(file_path)
+            .map_err(|err| CustomError::Config(format!("Invalid JSON format: {}", err)))
+    }
+
+    /// Parses the TOML configuration file into values keyed like config.json.
+    fn parse_file(content: &str) -> Result<HashMap<String, String>, String> {
+        let table: toml::Table = content.parse().map_err(|err| format!("{}", err))?;
+        let defaults = Config::defaults().map_err(|err| err.to_string())?;
+
+        table
+            .into_iter()
+            .map(|(key, value)| {
+                if defaults.get(&key).is_none() {
+                    return Err(format!("unknown key '{}'", key));
+                }
+                let value = match value {
+                    toml::Value::String(string) => string,
+                    // Lists like `fields = ["temperature", "humidity"]` are stored one item per
+                    // line, as items like the `http_headers` may contain commas.
+                    toml::Value::Array(items) => items
+                        .iter()
+                        .map(|item| match item {
+                            toml::Value::String(string) => string.clone(),
+                            other => other.to_string(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                    other => other.to_string(),
+                };
+                Ok((key, value))
+            })
+            .collect()
+    }
+
+    /// Collects the `WEATHER_WAND_*` environment variables that match a configuration key.
+    fn parse_env(
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> Result<HashMap<String, String>, CustomError> {
+        let defaults = Config::defaults()?;
+
+        Ok(vars
+            .filter_map(|(name, value)| {
+                let key = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+                defaults.get(&key).map(|_| (key, value))
+            })
+            .collect())
+    }
+}
+
+/// Replaces everything before the query of `template` with `base_url`, unless it is empty.
+fn with_base_url(template: &str, base_url: &str) -> String {
+    if base_url.is_empty() {
+        return template.to_string();
+    }
+
+    match template.split_once('?') {
+        Some((_, query)) => format!("{}?{}", base_url.trim_end_matches('?'), query),
+        None => base_url.to_string(),
+    }
+}
+
+/// Checks that `url` is an HTTP(S) URL without placeholders like `__LAT__` of older templates.
+/// They are no longer replaced, so they would be sent to the API as they are.
+fn check_api_url(key: &str, url: &str) -> Result<(), CustomError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|err| CustomError::Config(format!("{}: invalid URL '{}': {}", key, url, err)))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(CustomError::Config(format!(
+            "{}: '{}' is not an HTTP(S) URL",
+            key, url
+        )));
+    }
+
+    match parsed
+        .query_pairs()
+        .find(|(_, value)| value.len() > 4 && value.starts_with("__") && value.ends_with("__"))
+    {
+        Some((name, placeholder)) => Err(CustomError::Config(format!(
+            "{}: the URL '{}' contains the placeholder {}, remove the parameter '{}' as it is set by name",
+            key, url, placeholder, name
+        ))),
+        None => Ok(()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use super::{check_api_url, with_base_url, Config};
+    use crate::errors::CustomError;
 
     #[test]
     fn get_value_returns_correct_geo_api_url() {
         assert_eq!(
-            Config::get_value("geo_api_url").unwrap(), 
-            "https://geocoding-api.open-meteo.com/v1/search?name=__NAME__&count=1&language=en&format=json"
+            Config::get_value("geo_api_url").unwrap(),
+            "https://geocoding-api.open-meteo.com/v1/search?count=10&language=en&format=json"
         );
     }
 
@@ -60,7 +262,148 @@ mod tests {
     fn get_value_returns_correct_weather_api_url() {
         assert_eq!(
             Config::get_value("weather_api_url").unwrap(),
-            "https://api.open-meteo.com/v1/forecast?latitude=__LAT__&longitude=__LON__&current_weather=true&temperature_unit=__TEMPERATURE_UNIT__&timezone=auto&windspeed_unit=__WINDSPEED_UNIT__&timeformat=unixtime"
+            "https://api.open-meteo.com/v1/forecast?current=temperature_2m,windspeed_10m,winddirection_10m,weathercode,is_day&timezone=auto&timeformat=unixtime"
+        );
+    }
+
+    #[test]
+    fn get_value_returns_correct_daily_weather_api_url() {
+        assert_eq!(
+            Config::get_value("daily_weather_api_url").unwrap(),
+            "https://api.open-meteo.com/v1/forecast?daily=weathercode,temperature_2m_max,temperature_2m_min,precipitation_sum,windspeed_10m_max,sunrise,sunset&timezone=auto&timeformat=unixtime"
         );
     }
+
+    #[test]
+    fn get_value_returns_correct_hourly_weather_api_url() {
+        assert_eq!(
+            Config::get_value("hourly_weather_api_url").unwrap(),
+            "https://api.open-meteo.com/v1/forecast?hourly=temperature_2m,precipitation_probability,windspeed_10m,winddirection_10m,cloudcover&timezone=auto&timeformat=unixtime"
+        );
+    }
+
+    #[test]
+    fn get_value_returns_http_client_defaults() {
+        assert_eq!(Config::get_value("http_timeout_seconds").unwrap(), "2");
+        assert_eq!(
+            Config::get_value("http_user_agent").unwrap(),
+            "weather-wand"
+        );
+        assert_eq!(Config::get_value("http_proxy").unwrap(), "");
+        assert_eq!(Config::get_value("http_retries").unwrap(), "2");
+        assert_eq!(
+            Config::get_value("http_retry_base_delay_ms").unwrap(),
+            "500"
+        );
+        assert_eq!(
+            Config::get_value("http_retry_max_wait_seconds").unwrap(),
+            "10"
+        );
+    }
+
+    #[test]
+    fn get_number_parses_cache_ttls() {
+        assert_eq!(
+            Config::get_number("cache_ttl_geolocation_seconds").unwrap(),
+            2592000
+        );
+        assert_eq!(
+            Config::get_number("cache_ttl_weather_seconds").unwrap(),
+            900
+        );
+    }
+
+    #[test]
+    fn get_number_returns_config_error_for_non_numbers() {
+        let result = Config::get_number("http_user_agent");
+
+        assert!(matches!(result, Err(CustomError::Config(_))));
+    }
+
+    #[test]
+    fn parse_file_reads_known_keys_of_any_type() {
+        let content = r#"
+            city = "Frankfurt am Main"
+            temperature_unit = "fahrenheit"
+            http_timeout_seconds = 10
+            fields = ["temperature", "pressure"]
+        "#;
+
+        let values = Config::parse_file(content).unwrap();
+
+        assert_eq!(values["city"], "Frankfurt am Main");
+        assert_eq!(values["temperature_unit"], "fahrenheit");
+        assert_eq!(values["http_timeout_seconds"], "10");
+        assert_eq!(values["fields"], "temperature\npressure");
+    }
+
+    #[test]
+    fn parse_file_rejects_unknown_keys() {
+        let result = Config::parse_file(r#"temprature_unit = "fahrenheit""#);
+
+        assert!(result.unwrap_err().contains("temprature_unit"));
+    }
+
+    #[test]
+    fn parse_env_reads_prefixed_known_keys() {
+        let vars = vec![
+            ("WEATHER_WAND_WINDSPEED_UNIT".to_string(), "mph".to_string()),
+            ("WEATHER_WAND_UNKNOWN".to_string(), "ignored".to_string()),
+            ("CITY".to_string(), "ignored".to_string()),
+        ];
+
+        let values = Config::parse_env(vars.into_iter()).unwrap();
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(values["windspeed_unit"], "mph");
+    }
+
+    #[test]
+    fn get_value_returns_config_error_for_unknown_key() {
+        let result = Config::get_value("unknown_key");
+
+        assert!(matches!(result, Err(CustomError::Config(_))));
+    }
+
+    #[test]
+    fn get_api_url_uses_template_without_base_url() {
+        assert_eq!(
+            Config::get_api_url("geo_api_url").unwrap(),
+            Config::get_value("geo_api_url").unwrap()
+        );
+        assert!(Config::validate_api_urls().is_ok());
+    }
+
+    #[test]
+    fn with_base_url_replaces_everything_before_the_query() {
+        assert_eq!(
+            with_base_url(
+                "https://api.open-meteo.com/v1/forecast?timezone=auto",
+                "http://127.0.0.1:8080/v1/forecast"
+            ),
+            "http://127.0.0.1:8080/v1/forecast?timezone=auto"
+        );
+        assert_eq!(
+            with_base_url("https://api.open-meteo.com/v1/forecast?timezone=auto", ""),
+            "https://api.open-meteo.com/v1/forecast?timezone=auto"
+        );
+    }
+
+    #[test]
+    fn check_api_url_reports_leftover_placeholder() {
+        let result = check_api_url(
+            "weather_api_url",
+            "http://localhost/v1/forecast?lat=__LAT__&lon=__LON__",
+        );
+
+        assert!(matches!(&result, Err(CustomError::Config(_))));
+        assert!(result.unwrap_err().to_string().contains("__LAT__"));
+    }
+
+    #[test]
+    fn check_api_url_rejects_invalid_urls() {
+        assert!(check_api_url("geo_api_url", "localhost/search?count=10").is_err());
+        assert!(check_api_url("geo_api_url", "ftp://localhost/?count=10").is_err());
+        assert!(check_api_url("geo_api_url", "http://localhost/?count=10&format=json").is_ok());
+    }
 }
@@ -0,0 +1,241 @@
+use super::args::{
+    Args, ClockDisplay, Field, Language, OutputFormat, TemperatureUnit, WindspeedUnit,
+};
+use super::Config;
+use crate::errors::CustomError;
+use crate::geolocation::reverse::Backend;
+use crate::geolocation::{Coordinates, Gazetteer};
+use crate::locations::{SavedLocations, DEFAULT_ALIAS};
+use clap::ValueEnum;
+
+/// A struct representing the settings of a run after layering the command-line
+/// arguments over the environment, the user's configuration file and the defaults.
+pub struct Settings {
+    pub city: Vec<String>,
+    pub coordinates: Option<Coordinates>,
+    pub reverse_geocoder: Backend,
+    pub gazetteer: Gazetteer,
+    pub country: Option<String>,
+    pub temperature_unit: TemperatureUnit,
+    pub windspeed_unit: WindspeedUnit,
+    pub display: ClockDisplay,
+    pub language: Language,
+    pub output: OutputFormat,
+    pub fields: Vec<Field>,
+    pub days: Option<u8>,
+    pub hourly: Option<u8>,
+    pub pick: Option<u8>,
+}
+
+impl Settings {
+    /// Resolves the settings, preferring values given on the command line.
+    /// Without a city, the location saved as `default` in `saved` is used, then the configured city.
+    ///
+    /// # Errors
+    /// * No city is given, no default location is saved and no default city is configured.
+    /// * A configured unit, clock display, language, output format, field, reverse geocoder or gazetteer is not a possible value.
+    pub fn new(args: &Args, saved: &SavedLocations) -> Result<Self, CustomError> {
+        let coordinates = args.coordinates();
+
+        let city = if !args.city.is_empty() || coordinates.is_some() {
+            args.city.clone()
+        } else if saved.get(DEFAULT_ALIAS).is_some() {
+            vec![DEFAULT_ALIAS.to_string()]
+        } else {
+            let default_city = Config::get_value("city")?;
+            if default_city.is_empty() {
+                return Err(CustomError::Config(
+                    "no city given, pass --city, save a default location or set a default city in the configuration"
+                        .to_string(),
+                ));
+            }
+            vec![default_city]
+        };
+
+        let output = match (&args.output, args.compare) {
+            (_, true) => OutputFormat::Compare,
+            (Some(output), false) => output.clone(),
+            (None, false) => config_enum("output_format")?,
+        };
+
+        let country = match &args.country {
+            Some(country) => Some(country.clone()),
+            None => Some(Config::get_value("country")?).filter(|country| !country.is_empty()),
+        };
+
+        let fields = if args.fields.is_empty() {
+            config_list("fields")?
+        } else {
+            args.fields.clone()
+        };
+
+        Ok(Settings {
+            city,
+            coordinates,
+            reverse_geocoder: config_enum("reverse_geocoder")?,
+            gazetteer: config_enum("gazetteer")?,
+            country,
+            temperature_unit: or_config(&args.temperature_unit, "temperature_unit")?,
+            windspeed_unit: or_config(&args.windspeed_unit, "windspeed_unit")?,
+            display: or_config(&args.display, "clock_display")?,
+            language: or_config(&args.lang, "language")?,
+            output,
+            fields,
+            days: args.days,
+            hourly: args.hourly,
+            pick: args.pick,
+        })
+    }
+}
+
+/// Returns the value given on the command line, or the configured value of `key`.
+///
+/// # Errors
+/// * No value is given and the configured one is missing or not a possible value.
+pub fn or_config<T: ValueEnum + Clone>(arg: &Option<T>, key: &str) -> Result<T, CustomError> {
+    match arg {
+        Some(value) => Ok(value.clone()),
+        None => config_enum(key),
+    }
+}
+
+/// Reads the configured value of `key` as one of the possible values of `T`.
+///
+/// # Errors
+/// * The value is missing or not a possible value.
+pub fn config_enum<T: ValueEnum>(key: &str) -> Result<T, CustomError> {
+    let value = Config::get_value(key)?;
+
+    T::from_str(&value, true)
+        .map_err(|_| CustomError::Config(format!("{}: invalid value '{}'", key, value)))
+}
+
+/// Reads the configured list `key`, separated by commas or one item per line,
+/// as possible values of `T`.
+///
+/// # Errors
+/// * The value is missing or one of its items is not a possible value.
+pub fn config_list<T: ValueEnum>(key: &str) -> Result<Vec<T>, CustomError> {
+    Config::get_value(key)?
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            T::from_str(item, true)
+                .map_err(|_| CustomError::Config(format!("{}: invalid value '{}'", key, item)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Settings;
+    use crate::config::args::{Args, ClockDisplay, Field, OutputFormat, TemperatureUnit};
+    use crate::errors::CustomError;
+    use crate::geolocation::{Coordinates, Location};
+    use crate::locations::SavedLocations;
+    use clap::Parser;
+
+    fn no_saved_locations() -> SavedLocations {
+        SavedLocations::load(std::path::PathBuf::from("/nonexistent/locations.json")).unwrap()
+    }
+
+    #[test]
+    fn new_uses_defaults_for_missing_arguments() {
+        let args = Args::parse_from(["weather-wand", "-c", "Berlin"]);
+
+        let settings = Settings::new(&args, &no_saved_locations()).unwrap();
+
+        assert_eq!(settings.city, vec!["Berlin"]);
+        assert!(matches!(
+            settings.temperature_unit,
+            TemperatureUnit::Celsius
+        ));
+        assert!(matches!(settings.display, ClockDisplay::_24h));
+        assert_eq!(settings.output, OutputFormat::Report);
+        assert_eq!(settings.fields.first(), Some(&Field::Temperature));
+        assert!(settings.fields.contains(&Field::Humidity));
+        assert!(!settings.fields.contains(&Field::Pressure));
+    }
+
+    #[test]
+    fn new_prefers_command_line_arguments() {
+        let args = Args::parse_from([
+            "weather-wand",
+            "-c",
+            "Berlin",
+            "-t",
+            "fahrenheit",
+            "-d",
+            "12h",
+            "--compare",
+        ]);
+
+        let settings = Settings::new(&args, &no_saved_locations()).unwrap();
+
+        assert!(matches!(
+            settings.temperature_unit,
+            TemperatureUnit::Fahrenheit
+        ));
+        assert!(matches!(settings.display, ClockDisplay::_12h));
+        assert_eq!(settings.output, OutputFormat::Compare);
+    }
+
+    #[test]
+    fn new_reads_comma_separated_fields() {
+        let args = Args::parse_from([
+            "weather-wand",
+            "-c",
+            "Berlin",
+            "--fields",
+            "pressure,humidity",
+            "--fields",
+            "uv-index",
+        ]);
+
+        let settings = Settings::new(&args, &no_saved_locations()).unwrap();
+
+        assert_eq!(
+            settings.fields,
+            vec![Field::Pressure, Field::Humidity, Field::UvIndex]
+        );
+    }
+
+    #[test]
+    fn new_accepts_coordinates_instead_of_city() {
+        let args = Args::parse_from(["weather-wand", "--lat", "-33.87", "--lon", "151.21"]);
+
+        let settings = Settings::new(&args, &no_saved_locations()).unwrap();
+
+        assert!(settings.city.is_empty());
+        let coordinates = settings.coordinates.unwrap();
+        assert_eq!(coordinates.latitude(), -33.87);
+        assert_eq!(coordinates.longitude(), 151.21);
+    }
+
+    #[test]
+    fn new_without_city_returns_config_error() {
+        let args = Args::parse_from(["weather-wand"]);
+
+        assert!(matches!(
+            Settings::new(&args, &no_saved_locations()),
+            Err(CustomError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn new_without_city_uses_default_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut saved = SavedLocations::load(dir.path().join("locations.json")).unwrap();
+        saved
+            .add(
+                "default",
+                Location::from_coordinates(Coordinates::new(1.0, 2.0).unwrap()),
+            )
+            .unwrap();
+
+        let settings = Settings::new(&Args::parse_from(["weather-wand"]), &saved).unwrap();
+
+        assert_eq!(settings.city, vec!["default"]);
+    }
+}
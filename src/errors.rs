@@ -1,27 +1,306 @@
+use crate::client::resolver::LookupError;
 use std::error::Error;
 use std::fmt;
 
 /// Represents custom errors that may occur during weather and geolocation operations.
+///
+/// Every variant maps to its own process exit code, see [`CustomError::exit_code`].
 #[derive(Debug, Clone)]
 pub enum CustomError {
-    /// Indicates that the weather information could not be parsed correctly.
-    WeatherInfoNotFound(String),
+    /// Indicates that the request could not be sent or the connection to the API failed.
+    Network {
+        cause: NetworkCause,
+        message: String,
+    },
 
-    /// Indicates that the geolocation information could not be parsed correctly.
-    GeolocationNotFound(String),
+    /// Indicates that the API did not answer within the timeout.
+    Timeout(String),
+
+    /// Indicates that the API answered with a non-success HTTP status.
+    HttpStatus { status: u16, reason: String },
+
+    /// Indicates that the API reported an error inside an otherwise successful response.
+    Api(String),
+
+    /// Indicates that the API response could not be parsed as JSON.
+    MalformedJson(String),
+
+    /// Indicates that a required field is missing in the API response.
+    MissingField(String),
+
+    /// Indicates that the geolocation API did not find the requested city.
+    UnknownCity(String),
+
+    /// Indicates that the configuration is invalid or a value is missing.
+    Config(String),
+
+    /// Indicates that no stored response is available while working offline.
+    NotStored(String),
+
+    /// Indicates that the city name matches several places and none was chosen.
+    AmbiguousCity(String),
+
+    /// Indicates that the saved locations could not be read or changed, e.g. for an unknown alias.
+    SavedLocations(String),
+}
+
+impl CustomError {
+    /// Returns whether the failed request may succeed when it is sent again,
+    /// i.e. on timeouts, reset or refused connections and 5xx/429 responses.
+    /// Other network failures, e.g. a response body that cannot be read, are permanent.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            CustomError::Timeout(_) => true,
+            CustomError::Network { cause, .. } => matches!(
+                cause,
+                NetworkCause::ConnectionReset | NetworkCause::ConnectionRefused
+            ),
+            CustomError::HttpStatus { status, .. } => *status >= 500 || *status == 429,
+            _ => false,
+        }
+    }
+
+    /// Returns the process exit code for this error.
+    ///
+    /// | Code | Error             |
+    /// |------|-------------------|
+    /// | 3    | `Config`          |
+    /// | 4    | `UnknownCity`     |
+    /// | 5    | `Network`         |
+    /// | 6    | `Timeout`         |
+    /// | 7    | `HttpStatus`      |
+    /// | 8    | `Api`             |
+    /// | 9    | `MalformedJson`   |
+    /// | 10   | `MissingField`    |
+    /// | 11   | `NotStored`       |
+    /// | 12   | `AmbiguousCity`   |
+    /// | 13   | `SavedLocations`  |
+    ///
+    /// Code 1 is left for unexpected failures and code 2 is used by clap for invalid arguments.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CustomError::Config(_) => 3,
+            CustomError::UnknownCity(_) => 4,
+            CustomError::Network { .. } => 5,
+            CustomError::Timeout(_) => 6,
+            CustomError::HttpStatus { .. } => 7,
+            CustomError::Api(_) => 8,
+            CustomError::MalformedJson(_) => 9,
+            CustomError::MissingField(_) => 10,
+            CustomError::NotStored(_) => 11,
+            CustomError::AmbiguousCity(_) => 12,
+            CustomError::SavedLocations(_) => 13,
+        }
+    }
 }
 
 impl fmt::Display for CustomError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            CustomError::WeatherInfoNotFound(info) => {
-                write!(f, "Weather information not found - {}", info)
+            CustomError::Network { cause, message } => {
+                write!(f, "Failed to connect to the API ({}) - {}", cause, message)
             }
-            CustomError::GeolocationNotFound(info) => {
+            CustomError::Timeout(info) => {
+                write!(f, "The API did not respond in time - {}", info)
+            }
+            CustomError::HttpStatus { status, reason } => {
+                write!(
+                    f,
+                    "The API responded with HTTP status {} - {}",
+                    status, reason
+                )
+            }
+            CustomError::Api(info) => {
+                write!(f, "The API reported an error - {}", info)
+            }
+            CustomError::MalformedJson(info) => {
+                write!(f, "Error parsing JSON - {}", info)
+            }
+            CustomError::MissingField(info) => {
+                write!(
+                    f,
+                    "Required information not found in the API response - {}",
+                    info
+                )
+            }
+            CustomError::UnknownCity(info) => {
                 write!(f, "Geolocation information not found - {}", info)
             }
+            CustomError::Config(info) => {
+                write!(f, "Invalid configuration - {}", info)
+            }
+            CustomError::NotStored(info) => {
+                write!(f, "No stored weather data available offline - {}", info)
+            }
+            CustomError::AmbiguousCity(info) => {
+                write!(f, "The city name matches several places - {}", info)
+            }
+            CustomError::SavedLocations(info) => {
+                write!(f, "Saved locations unavailable - {}", info)
+            }
         }
     }
 }
 
 impl Error for CustomError {}
+
+/// The underlying cause of a `CustomError::Network` error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkCause {
+    /// The host name of the API could not be resolved.
+    Dns,
+
+    /// The API host actively refused the connection.
+    ConnectionRefused,
+
+    /// The connection was reset or closed while the request was in flight.
+    ConnectionReset,
+
+    /// The TLS handshake or certificate validation failed.
+    Tls,
+
+    /// Any other failure while sending the request or reading the response.
+    Other,
+}
+
+impl NetworkCause {
+    /// Determines the cause by the types in the source chain of a `reqwest::Error`.
+    /// Lookup failures are recognized by the `LookupError` of the client's resolver.
+    fn of(err: &reqwest::Error) -> Self {
+        let mut source: Option<&dyn Error> = Some(err);
+
+        while let Some(current) = source {
+            if current.is::<LookupError>() {
+                return NetworkCause::Dns;
+            }
+            if current.is::<native_tls::Error>() {
+                return NetworkCause::Tls;
+            }
+            if let Some(io_error) = current.downcast_ref::<std::io::Error>() {
+                match io_error.kind() {
+                    std::io::ErrorKind::ConnectionRefused => {
+                        return NetworkCause::ConnectionRefused
+                    }
+                    std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::ConnectionAborted => {
+                        return NetworkCause::ConnectionReset
+                    }
+                    _ => {}
+                }
+            }
+
+            source = current.source();
+        }
+
+        NetworkCause::Other
+    }
+}
+
+impl fmt::Display for NetworkCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            NetworkCause::Dns => "DNS lookup failed",
+            NetworkCause::ConnectionRefused => "connection refused",
+            NetworkCause::ConnectionReset => "connection reset",
+            NetworkCause::Tls => "TLS error",
+            NetworkCause::Other => "request failed",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl From<reqwest::Error> for CustomError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            return CustomError::Timeout(error_chain(&err));
+        }
+
+        CustomError::Network {
+            cause: NetworkCause::of(&err),
+            message: error_chain(&err),
+        }
+    }
+}
+
+/// Joins the messages of an error and all of its sources, e.g. "error sending request: tcp connect error: Connection refused".
+fn error_chain(err: &dyn Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+
+    while let Some(current) = source {
+        // Some errors already include their source in their own message.
+        let current_message = current.to_string();
+        if !message.contains(&current_message) {
+            message.push_str(&format!(": {}", current_message));
+        }
+        source = current.source();
+    }
+
+    message
+}
+
+impl From<serde_json::Error> for CustomError {
+    fn from(err: serde_json::Error) -> Self {
+        CustomError::MalformedJson(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomError, NetworkCause};
+
+    #[test]
+    fn exit_codes_are_distinct_and_non_zero() {
+        let errors = [
+            CustomError::Config(String::new()),
+            CustomError::UnknownCity(String::new()),
+            CustomError::Network {
+                cause: NetworkCause::Other,
+                message: String::new(),
+            },
+            CustomError::Timeout(String::new()),
+            CustomError::HttpStatus {
+                status: 500,
+                reason: String::new(),
+            },
+            CustomError::Api(String::new()),
+            CustomError::MalformedJson(String::new()),
+            CustomError::MissingField(String::new()),
+            CustomError::NotStored(String::new()),
+            CustomError::AmbiguousCity(String::new()),
+            CustomError::SavedLocations(String::new()),
+        ];
+
+        let mut codes: Vec<u8> = errors.iter().map(CustomError::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0));
+        assert!(!codes.contains(&1));
+        assert!(!codes.contains(&2));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retryable() {
+        let http_status = |status| CustomError::HttpStatus {
+            status,
+            reason: String::new(),
+        };
+        let network = |cause| CustomError::Network {
+            cause,
+            message: String::new(),
+        };
+
+        assert!(CustomError::Timeout(String::new()).is_retryable());
+        assert!(network(NetworkCause::ConnectionReset).is_retryable());
+        assert!(network(NetworkCause::ConnectionRefused).is_retryable());
+        assert!(http_status(503).is_retryable());
+        assert!(http_status(429).is_retryable());
+
+        assert!(!network(NetworkCause::Dns).is_retryable());
+        assert!(!network(NetworkCause::Tls).is_retryable());
+        assert!(!network(NetworkCause::Other).is_retryable());
+        assert!(!http_status(404).is_retryable());
+        assert!(!CustomError::UnknownCity(String::new()).is_retryable());
+    }
+}
@@ -0,0 +1,283 @@
+use super::query::LocationQuery;
+use super::{Coordinates, Location};
+use std::sync::OnceLock;
+
+/// The bundled city table, see files/cities.tsv for its columns.
+const CITIES: &str = include_str!("files/cities.tsv");
+
+/// A line of the bundled table with the names of its city normalized for matching.
+struct City {
+    names: Vec<String>,
+    line: &'static str,
+}
+
+static TABLE: OnceLock<Vec<City>> = OnceLock::new();
+
+/// Looks up the places matching `query` in the bundled city table, without using the network.
+///
+/// Names and alternate names are compared ignoring case and accents. Exact matches are preferred
+/// over names starting with the query, e.g. "Frankfurt am Main" for "frankfurt". The places found
+/// are sorted by population.
+///
+/// # Returns
+/// Returns the matching places having all qualifiers of the query, or `None` if there are none,
+/// so that the geolocation API can be asked instead.
+pub fn search(query: &LocationQuery) -> Option<Vec<Location>> {
+    search_with(query, false)
+}
+
+/// Looks up the places matching `query` like `search`, but also accepts misspelled names,
+/// e.g. "Müncen". Only meant for when the geolocation API cannot be reached, since a typo of
+/// a city missing from the table often is the name of another one, e.g. "Kiel" of "Kiev".
+pub fn search_fuzzy(query: &LocationQuery) -> Option<Vec<Location>> {
+    search_with(query, true)
+}
+
+fn search_with(query: &LocationQuery, fuzzy: bool) -> Option<Vec<Location>> {
+    let name = normalize(&query.name);
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut scored: Vec<(usize, Location)> = table()
+        .iter()
+        .filter_map(|city| {
+            let score = city
+                .names
+                .iter()
+                .filter_map(|candidate| score(&name, candidate, fuzzy))
+                .min()?;
+            Some((score, parse_line(city.line)?))
+        })
+        .filter(|(_, location)| query.matches(location))
+        .collect();
+
+    let best = scored.iter().map(|(score, _)| *score).min()?;
+    scored.retain(|(score, _)| *score == best);
+    scored.sort_by_key(|(_, location)| std::cmp::Reverse(location.population));
+
+    Some(scored.into_iter().map(|(_, location)| location).collect())
+}
+
+/// Rates how well `candidate` matches the normalized `query`, lower is better:
+/// 0 for the same name, 1 if the name starts with the query and, if `fuzzy`, 1 + the number
+/// of typos otherwise.
+fn score(query: &str, candidate: &str, fuzzy: bool) -> Option<usize> {
+    if candidate == query {
+        return Some(0);
+    }
+
+    if candidate
+        .strip_prefix(query)
+        .is_some_and(|rest| rest.starts_with(|char: char| !char.is_alphanumeric()))
+    {
+        return Some(1);
+    }
+
+    if !fuzzy {
+        return None;
+    }
+
+    // Allow one typo per four characters, so short names must be exact.
+    let allowed = query.chars().count() / 4;
+    let distance = edit_distance(query, candidate);
+    (distance <= allowed).then_some(1 + distance)
+}
+
+/// Counts the insertions, deletions, substitutions and swaps of adjacent characters needed
+/// to turn `a` into `b` (optimal string alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            rows[i][j] = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Lowercases `name`, strips common accents and collapses whitespace, e.g. "Saint-Étienne" to "saint-etienne".
+fn normalize(name: &str) -> String {
+    let folded: String = name
+        .to_lowercase()
+        .chars()
+        .map(|char| match char {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a".to_string(),
+            'ç' | 'ć' | 'č' => "c".to_string(),
+            'ď' | 'đ' => "d".to_string(),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e".to_string(),
+            'ğ' => "g".to_string(),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i".to_string(),
+            'ł' => "l".to_string(),
+            'ñ' | 'ń' | 'ň' => "n".to_string(),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o".to_string(),
+            'ř' => "r".to_string(),
+            'ś' | 'ş' | 'š' | 'ș' => "s".to_string(),
+            'ß' => "ss".to_string(),
+            'ţ' | 'ť' | 'ț' => "t".to_string(),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u".to_string(),
+            'ý' | 'ÿ' => "y".to_string(),
+            'ź' | 'ż' | 'ž' => "z".to_string(),
+            // The dotted capital İ lowercases to "i" followed by a combining dot.
+            '\u{307}' => String::new(),
+            other => other.to_string(),
+        })
+        .collect();
+
+    folded.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn table() -> &'static Vec<City> {
+    TABLE.get_or_init(|| {
+        CITIES
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .map(|line| {
+                let mut columns = line.split('\t');
+                let name = columns.next().unwrap_or_default();
+                let alternate_names = columns.next().unwrap_or_default();
+
+                City {
+                    names: std::iter::once(name)
+                        .chain(alternate_names.split(','))
+                        .filter(|name| !name.is_empty())
+                        .map(normalize)
+                        .collect(),
+                    line,
+                }
+            })
+            .collect()
+    })
+}
+
+/// Parses a line of the city table into a `Location`, skipping malformed lines.
+fn parse_line(line: &str) -> Option<Location> {
+    let columns: Vec<&str> = line.split('\t').collect();
+    let [name, _, region, country, country_code, latitude, longitude, timezone, population] =
+        columns[..]
+    else {
+        return None;
+    };
+
+    let coordinates = Coordinates::new(latitude.parse().ok()?, longitude.parse().ok()?).ok()?;
+
+    Some(Location {
+        name: name.to_string(),
+        country_code: country_code.to_string(),
+        timezone: timezone.to_string(),
+        coordinates,
+        region: (!region.is_empty()).then(|| region.to_string()),
+        district: None,
+        country: country.to_string(),
+        population: population.parse().ok(),
+        elevation: None,
+        feature_code: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, normalize, search, search_fuzzy, table};
+    use crate::geolocation::query::LocationQuery;
+
+    fn names(query: &str, country: Option<&str>) -> Vec<String> {
+        search(&LocationQuery::parse(query, country))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|location| location.title())
+            .collect()
+    }
+
+    fn fuzzy_names(query: &str) -> Vec<String> {
+        search_fuzzy(&LocationQuery::parse(query, None))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|location| location.title())
+            .collect()
+    }
+
+    #[test]
+    fn every_line_of_the_table_is_valid() {
+        for city in table() {
+            let location = super::parse_line(city.line).expect(city.line);
+            assert!(location.population.is_some(), "{}", city.line);
+        }
+    }
+
+    #[test]
+    fn search_finds_names_and_alternate_names_ignoring_case_and_accents() {
+        assert_eq!(names("berlin", None), vec!["Berlin, DE"]);
+        assert_eq!(names("Saint-Etienne", None), vec!["Saint-Étienne, FR"]);
+        assert_eq!(names("sanliurfa", None), vec!["Şanlıurfa, TR"]);
+        assert_eq!(names("Köln", None), vec!["Cologne, DE"]);
+        assert_eq!(names("NYC", None), vec!["New York City, US"]);
+    }
+
+    #[test]
+    fn search_leaves_unknown_and_misspelled_names_to_the_api() {
+        for name in ["Kiel", "Bern", "Riga", "Nice", "Muenchn", "Xyzzy"] {
+            assert!(
+                search(&LocationQuery::parse(name, None)).is_none(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn search_fuzzy_tolerates_typos() {
+        assert_eq!(fuzzy_names("Muenchn"), vec!["Munich, DE"]);
+        assert_eq!(fuzzy_names("Stcokholm"), vec!["Stockholm, SE"]);
+        assert_eq!(fuzzy_names("berlin"), vec!["Berlin, DE"]);
+        assert!(fuzzy_names("Bern").is_empty());
+        assert!(fuzzy_names("Riga").is_empty());
+        assert!(fuzzy_names("Xyzzy").is_empty());
+    }
+
+    #[test]
+    fn search_returns_all_equally_good_matches_by_population() {
+        assert_eq!(
+            names("Springfield", None),
+            vec!["Springfield, US", "Springfield, US", "Springfield, US"]
+        );
+        assert_eq!(
+            names("Frankfurt", None),
+            vec!["Frankfurt am Main, DE", "Frankfurt (Oder), DE"]
+        );
+    }
+
+    #[test]
+    fn search_applies_qualifiers() {
+        let portland = search(&LocationQuery::parse("Portland, ME", None)).unwrap();
+
+        assert_eq!(portland.len(), 1);
+        assert_eq!(portland[0].region.as_deref(), Some("Maine"));
+        assert_eq!(names("Paris", Some("FR")), vec!["Paris, FR"]);
+        assert!(search(&LocationQuery::parse("Paris, DE", None)).is_none());
+    }
+
+    #[test]
+    fn normalize_folds_accents_and_whitespace() {
+        assert_eq!(normalize(" İzmir "), "izmir");
+        assert_eq!(normalize("São  Paulo"), "sao paulo");
+        assert_eq!(edit_distance("stcokholm", "stockholm"), 1);
+        assert_eq!(edit_distance("", "oslo"), 4);
+    }
+}
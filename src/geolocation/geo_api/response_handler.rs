@@ -1,89 +1,296 @@
 use super::Coordinates;
 use super::Location;
 use crate::errors::CustomError;
+use serde::Deserialize;
 use serde_json::Value;
 
-/// Deserializes a JSON string into a `Location` struct.
+/// The response of the geolocation API, see <https://open-meteo.com/en/docs/geocoding-api>.
+/// The results are kept as JSON, so that an incomplete result doesn't fail the others.
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    error: bool,
+    reason: Option<String>,
+    #[serde(default)]
+    results: Vec<Value>,
+}
+
+/// A place found by the geolocation API. Only its name and coordinates are always known;
+/// e.g. islands and research stations often lack an `admin1` or even a country.
+#[derive(Deserialize)]
+struct SearchResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    #[serde(default)]
+    timezone: String,
+    #[serde(default)]
+    country_code: String,
+    #[serde(default)]
+    country: String,
+    admin1: Option<String>,
+    admin2: Option<String>,
+    population: Option<u64>,
+    elevation: Option<f64>,
+    feature_code: Option<String>,
+}
+
+impl TryFrom<SearchResult> for Location {
+    type Error = String;
+
+    fn try_from(result: SearchResult) -> Result<Self, Self::Error> {
+        Ok(Location {
+            name: result.name,
+            country_code: result.country_code,
+            timezone: result.timezone,
+            coordinates: Coordinates::new(result.latitude, result.longitude)?,
+            region: result.admin1,
+            district: result.admin2,
+            country: result.country,
+            population: result.population,
+            elevation: result.elevation,
+            feature_code: result.feature_code,
+        })
+    }
+}
+
+/// Deserializes a JSON string into the list of `Location` structs matching a search.
 ///
 /// # Arguments
 /// * `body`: A `Result` containing a JSON string or an error.
 ///
 /// # Returns
-/// Returns a `Result` containing a `Location` instance if deserialization is successful,
+/// Returns a `Result` containing the matching `Location` instances in the order of the API,
 /// or an error if the JSON structure is invalid or geolocation information is missing.
+/// Results without a name or valid coordinates are skipped.
 ///
 /// # Errors
 /// This function can return errors in the following scenarios:
 /// * The JSON string cannot be parsed.
 /// * No results are found in the JSON structure.
 /// * An error field is found in the JSON structure.
-pub fn deserialize(
-    body: Result<String, Box<dyn std::error::Error>>,
-) -> Result<Location, Box<dyn std::error::Error>> {
-    let parsed_body: Value =
-        serde_json::from_str(&body?).map_err(|err| format!("Error parsing JSON: {}", err))?;
-
-    if parsed_body["error"] == true || parsed_body["results"].is_null() {
-        return Err(Box::new(CustomError::GeolocationNotFound(
-            "results".to_string(),
-        )));
-    }
-
-    let city_name = &parsed_body["results"][0]["name"].as_str().unwrap();
-    let timezone = &parsed_body["results"][0]["timezone"].as_str().unwrap();
-    let country_code = &parsed_body["results"][0]["country_code"].as_str().unwrap();
-    let latitude = &parsed_body["results"][0]["latitude"].as_f64().unwrap();
-    let longitude = &parsed_body["results"][0]["longitude"].as_f64().unwrap();
-    let region = &parsed_body["results"][0]["admin1"].as_str().unwrap();
-    let country = &parsed_body["results"][0]["country"].as_str().unwrap();
-
-    let location: Location = Location {
-        name: city_name.to_string(),
-        country_code: country_code.to_string(),
-        timezone: timezone.to_string(),
-        coordinates: Coordinates {
-            latitude: latitude.to_string(),
-            longitude: longitude.to_string(),
-        },
-        region: region.to_string(),
-        country: country.to_string(),
+/// * No result has a name and coordinates.
+pub fn deserialize(body: Result<String, CustomError>) -> Result<Vec<Location>, CustomError> {
+    let response: SearchResponse = serde_json::from_str(&body?)?;
+
+    if response.error {
+        return Err(CustomError::Api(
+            response.reason.unwrap_or_else(|| "unknown".to_string()),
+        ));
+    }
+
+    if response.results.is_empty() {
+        return Err(CustomError::UnknownCity("no matching results".to_string()));
+    }
+
+    let mut locations = Vec::new();
+    let mut first_error = None;
+    for (index, result) in response.results.into_iter().enumerate() {
+        let location = serde_json::from_value::<SearchResult>(result)
+            .map_err(|err| err.to_string())
+            .and_then(Location::try_from);
+
+        match location {
+            Ok(location) => locations.push(location),
+            Err(e) => {
+                first_error.get_or_insert(CustomError::MissingField(format!(
+                    "results[{}]: {}",
+                    index, e
+                )));
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) if locations.is_empty() => Err(e),
+        _ => Ok(locations),
+    }
+}
+
+/// Deserializes a JSON string of the Nominatim reverse geocoding API into a `Location` struct.
+///
+/// The place is named after the most specific of its city, town, village, municipality or county.
+/// Its `timezone` is left empty, as the API does not know it.
+///
+/// # Arguments
+/// * `body`: A `Result` containing a JSON string or an error.
+///
+/// # Errors
+/// This function can return errors in the following scenarios:
+/// * The JSON string cannot be parsed.
+/// * The API reports that there is no place at the coordinates.
+/// * The coordinates or the address of the place are missing.
+pub fn deserialize_reverse(body: Result<String, CustomError>) -> Result<Location, CustomError> {
+    let parsed_body: Value = serde_json::from_str(&body?)?;
+
+    if let Some(reason) = parsed_body["error"].as_str() {
+        return Err(CustomError::UnknownCity(reason.to_string()));
+    }
+
+    let address = &parsed_body["address"];
+    if !address.is_object() {
+        return Err(CustomError::MissingField("address".to_string()));
+    }
+
+    let name = ["city", "town", "village", "municipality", "county"]
+        .iter()
+        .find_map(|key| address[key].as_str())
+        .or_else(|| parsed_body["name"].as_str())
+        .ok_or_else(|| CustomError::MissingField("address.city".to_string()))?;
+    let address_field = |key: &str| address[key].as_str().unwrap_or_default().to_string();
+    // Nominatim returns the coordinates as strings.
+    let coordinate = |key: &str| {
+        parsed_body[key]
+            .as_str()
+            .and_then(|degrees| degrees.parse::<f64>().ok())
+            .ok_or_else(|| CustomError::MissingField(key.to_string()))
     };
+    let coordinates = Coordinates::new(coordinate("lat")?, coordinate("lon")?)
+        .map_err(|err| CustomError::MissingField(format!("lat/lon: {}", err)))?;
 
-    Ok(location)
+    Ok(Location {
+        name: name.to_string(),
+        country_code: address_field("country_code").to_uppercase(),
+        timezone: String::new(),
+        coordinates,
+        region: address["state"].as_str().map(str::to_string),
+        district: address["county"].as_str().map(str::to_string),
+        country: address_field("country"),
+        population: None,
+        elevation: None,
+        feature_code: None,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::deserialize;
+    use super::{deserialize, deserialize_reverse};
+    use crate::errors::CustomError;
 
     #[test]
     fn deserialize_creates_correct_location_struct() {
         let json =
             r#"{"results":[{"name":"New York","latitude":40.71427,"longitude":-74.00597,"country_code":"US","timezone":"America/New_York","country":"United States","admin1":"New York"}]}"#.to_string();
 
-        let result = deserialize(Ok(json)).unwrap();
+        let results = deserialize(Ok(json)).unwrap();
+        let result = &results[0];
 
+        assert_eq!(results.len(), 1);
         assert_eq!(result.name, "New York");
-        assert_eq!(result.coordinates.latitude, "40.71427");
-        assert_eq!(result.coordinates.longitude, "-74.00597");
+        assert_eq!(result.coordinates.latitude(), 40.71427);
+        assert_eq!(result.coordinates.longitude(), -74.00597);
         assert_eq!(result.country_code, "US");
         assert_eq!(result.timezone, "America/New_York");
-        assert_eq!(result.region, "New York");
+        assert_eq!(result.region.as_deref(), Some("New York"));
         assert_eq!(result.country, "United States");
+        assert_eq!(result.population, None);
+    }
+
+    #[test]
+    fn deserialize_keeps_optional_fields() {
+        let json = r#"{"results":[{"id":2950159,"name":"Berlin","latitude":52.52437,"longitude":13.41053,"elevation":74.0,"feature_code":"PPLC","country_code":"DE","admin1_id":2950157,"timezone":"Europe/Berlin","population":3426354,"country":"Germany","admin1":"Land Berlin","admin2":"Berlin, Stadt"}]}"#.to_string();
+
+        let result = &deserialize(Ok(json)).unwrap()[0];
+
+        assert_eq!(result.population, Some(3426354));
+        assert_eq!(result.elevation, Some(74.0));
+        assert_eq!(result.feature_code.as_deref(), Some("PPLC"));
+        assert_eq!(result.district.as_deref(), Some("Berlin, Stadt"));
+    }
+
+    #[test]
+    fn deserialize_accepts_results_without_region_or_timezone() {
+        let json = r#"{"results":[{"name":"McMurdo Station","latitude":-77.846,"longitude":166.676,"feature_code":"STNB","country_code":"AQ","country":"Antarctica"}]}"#.to_string();
+
+        let result = &deserialize(Ok(json)).unwrap()[0];
+
+        assert_eq!(result.title(), "McMurdo Station, AQ");
+        assert_eq!(result.region, None);
+        assert_eq!(result.timezone, "");
+    }
+
+    #[test]
+    fn deserialize_returns_results_with_and_without_region() {
+        let json = r#"{"results":[
+            {"name":"Frankfurt am Main","latitude":50.11552,"longitude":8.68417,"country_code":"DE","timezone":"Europe/Berlin","country":"Germany","admin1":"Hesse","population":650000},
+            {"name":"Frankfurt","latitude":38.2009,"longitude":-84.87328,"country_code":"US","timezone":"America/New_York","country":"United States"},
+            {"name":"Frankfurt (Oder)","latitude":52.34714,"longitude":14.55062,"country_code":"DE","timezone":"Europe/Berlin","country":"Germany","admin1":"Brandenburg","population":57751}
+        ]}"#.to_string();
+
+        let results = deserialize(Ok(json)).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].population, Some(650000));
+        assert_eq!(results[1].region, None);
+        assert_eq!(results[2].name, "Frankfurt (Oder)");
+        assert_eq!(results[2].region.as_deref(), Some("Brandenburg"));
     }
 
     #[test]
     fn deserialize_throws_error_on_invalid_city_name() {
         // The geolocation API returns the "generationtime_ms" JSON field if the city name is invalid,
-        // so we use this to trigger the GeolocationNotFound error.
+        // so we use this to trigger the UnknownCity error.
         let result = deserialize(Ok(r#"{"generationtime_ms": 0.0}"#.to_string()));
 
         assert!(result.is_err());
 
         if let Some(err) = result.err() {
+            assert!(matches!(err, CustomError::UnknownCity(_)));
             assert!(err
                 .to_string()
                 .contains("Geolocation information not found"));
         }
     }
+
+    #[test]
+    fn deserialize_throws_error_on_missing_field() {
+        let json = r#"{"results":[{"name":"New York","latitude":40.71427,"country_code":"US"}]}"#
+            .to_string();
+
+        let result = deserialize(Ok(json));
+
+        assert!(matches!(result, Err(CustomError::MissingField(_))));
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("results[0]: missing field `longitude`"));
+    }
+
+    #[test]
+    fn deserialize_throws_error_on_malformed_json() {
+        let result = deserialize(Ok("not json".to_string()));
+
+        assert!(matches!(result, Err(CustomError::MalformedJson(_))));
+    }
+
+    #[test]
+    fn deserialize_reverse_creates_correct_location_struct() {
+        let json = r#"{"lat":"52.5173885","lon":"13.3951309","name":"Mitte","address":{"suburb":"Mitte","city":"Berlin","state":"Berlin","country":"Germany","country_code":"de"}}"#.to_string();
+
+        let result = deserialize_reverse(Ok(json)).unwrap();
+
+        assert_eq!(result.name, "Berlin");
+        assert_eq!(result.region.as_deref(), Some("Berlin"));
+        assert_eq!(result.country, "Germany");
+        assert_eq!(result.country_code, "DE");
+        assert_eq!(result.coordinates.latitude(), 52.5173885);
+    }
+
+    #[test]
+    fn deserialize_reverse_falls_back_to_smaller_places() {
+        let json = r#"{"lat":"64.1","lon":"-21.9","address":{"village":"Vík","country":"Iceland","country_code":"is"}}"#.to_string();
+
+        let result = deserialize_reverse(Ok(json)).unwrap();
+
+        assert_eq!(result.name, "Vík");
+        assert_eq!(result.region, None);
+    }
+
+    #[test]
+    fn deserialize_reverse_throws_error_without_place() {
+        let result = deserialize_reverse(Ok(r#"{"error":"Unable to geocode"}"#.to_string()));
+
+        assert!(matches!(result, Err(CustomError::UnknownCity(_))));
+    }
 }
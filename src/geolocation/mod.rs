@@ -1,67 +1,323 @@
+#[cfg(feature = "gazetteer")]
+pub mod gazetteer;
 pub mod geo_api;
+pub mod query;
+pub mod reverse;
+pub mod selection;
 
+use super::config::args::Language;
 use super::config::Config;
+use crate::client::query::{parse_url, set_param};
+use crate::client::Client;
+use crate::errors::CustomError;
 use geo_api::response_handler;
+use query::LocationQuery;
+use reqwest::Url;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::fmt::Display;
+use std::time::Duration;
 
-/// A struct representing a cities coordinates using `latitude` and `longitude`
+/// A struct representing a cities coordinates using `latitude` and `longitude` in decimal degrees.
+/// Both are checked to be in range, so every `Coordinates` instance is a valid point on earth.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
 pub struct Coordinates {
-    pub latitude: String,
-    pub longitude: String,
+    latitude: f64,
+    longitude: f64,
 }
 
-/// Asynchronously retrieves location information for a given location string.
+impl Coordinates {
+    /// Instantiate new `Coordinates` from decimal degrees.
+    ///
+    /// # Errors
+    /// * The latitude is not between -90 and 90 or the longitude not between -180 and 180.
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self, String> {
+        Ok(Coordinates {
+            latitude: check_degrees(latitude, 90.0)?,
+            longitude: check_degrees(longitude, 180.0)?,
+        })
+    }
+
+    /// Returns the latitude in decimal degrees, positive north of the equator.
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Returns the longitude in decimal degrees, positive east of Greenwich.
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+}
+
+impl Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", self.latitude, self.longitude)
+    }
+}
+
+impl<'de> Deserialize<'de> for Coordinates {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Degrees {
+            latitude: f64,
+            longitude: f64,
+        }
+
+        let degrees = Degrees::deserialize(deserializer)?;
+        Coordinates::new(degrees.latitude, degrees.longitude).map_err(de::Error::custom)
+    }
+}
+
+/// Parses a latitude in decimal degrees, e.g. `52.52`.
+pub fn parse_latitude(latitude: &str) -> Result<f64, String> {
+    check_degrees(parse_number(latitude)?, 90.0)
+}
+
+/// Parses a longitude in decimal degrees, e.g. `-13.41`.
+pub fn parse_longitude(longitude: &str) -> Result<f64, String> {
+    check_degrees(parse_number(longitude)?, 180.0)
+}
+
+/// Parses coordinates given as `LAT,LON` in decimal degrees, e.g. `52.52,13.41`.
+pub fn parse_coordinates(coordinates: &str) -> Result<Coordinates, String> {
+    let (latitude, longitude) = coordinates
+        .split_once(',')
+        .ok_or_else(|| format!("expected LAT,LON but got '{}'", coordinates))?;
+
+    Coordinates::new(parse_number(latitude)?, parse_number(longitude)?)
+}
+
+fn parse_number(degrees: &str) -> Result<f64, String> {
+    degrees
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", degrees.trim()))
+}
+
+fn check_degrees(value: f64, limit: f64) -> Result<f64, String> {
+    if !(-limit..=limit).contains(&value) {
+        return Err(format!("{} is not between -{} and {}", value, limit, limit));
+    }
+
+    Ok(value)
+}
+
+/// The number of places requested for a qualified query, so the wanted place is likely among them.
+const QUALIFIED_CANDIDATES: u8 = 50;
+
+/// Whether the bundled city table of the `gazetteer` feature is used, configured with `gazetteer`.
+/// Without the feature, it is never used.
+#[derive(Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+pub enum Gazetteer {
+    /// Use the table for names it knows exactly, and for misspelled names if the API cannot be reached.
+    On,
+    /// Always ask the geolocation API.
+    Off,
+}
+
+impl Display for Gazetteer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Gazetteer::On => "on".to_string(),
+            Gazetteer::Off => "off".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Asynchronously retrieves the places matching a given location query.
+/// With the `gazetteer` feature, the bundled city table is searched first for English names.
 ///
 /// # Arguments
-/// * `location`: The location for which to retrieve information.
+/// * `client`: The HTTP client used for the request.
+/// * `query`: The location for which to retrieve information, with the qualifiers a place must have.
+/// * `language`: The language of the returned place names.
+/// * `gazetteer`: Whether the bundled city table may be used.
 ///
 /// # Returns
-/// Returns a `Result` containing a `Location` instance if successful,
+/// Returns a `Result` containing the matching `Location` instances if successful,
 /// or an error if the retrieval fails, the URL is invalid, or the API response is malformed.
 ///
 /// # Errors
 /// This function can return errors in the following scenarios:
 /// * The URL construction or modification fails.
 /// * The HTTP request to the Geolocation API fails.
-/// * Deserialization of the API response into a `Location` struct fails.
-pub async fn get_info_for(location: &str) -> Result<Location, Box<dyn std::error::Error>> {
-    let base_url = Config::get_value("geo_api_url")?;
-    let mut url_unmodified = GeoApiUrl::new(base_url);
+/// * Deserialization of the API response into `Location` structs fails.
+/// * No place has all qualifiers of the query.
+pub async fn get_info_for(
+    client: &Client,
+    query: &LocationQuery,
+    language: &Language,
+    #[cfg_attr(not(feature = "gazetteer"), allow(unused_variables))] gazetteer: &Gazetteer,
+) -> Result<Vec<Location>, CustomError> {
+    // The bundled table only knows the English names of its cities.
+    #[cfg(feature = "gazetteer")]
+    let local = *gazetteer == Gazetteer::On && *language == Language::En;
+
+    #[cfg(feature = "gazetteer")]
+    if local {
+        if let Some(locations) = gazetteer::search(query) {
+            return Ok(locations);
+        }
+    }
 
-    let url = &url_unmodified.set_location(location)?.url;
+    let result = search_api(client, query, language).await;
 
-    let geo_info = response_handler::deserialize(geo_api::Client::fetch(url).await)?;
+    // Misspelled names are only guessed from the table if the API cannot answer.
+    #[cfg(feature = "gazetteer")]
+    if let Err(CustomError::Network { .. } | CustomError::Timeout(_) | CustomError::NotStored(_)) =
+        &result
+    {
+        if local {
+            if let Some(locations) = gazetteer::search_fuzzy(query) {
+                return Ok(locations);
+            }
+        }
+    }
+
+    result
+}
+
+/// Asks the geolocation API for the places matching `query`, see `get_info_for`.
+async fn search_api(
+    client: &Client,
+    query: &LocationQuery,
+    language: &Language,
+) -> Result<Vec<Location>, CustomError> {
+    let base_url = Config::get_api_url("geo_api_url")?;
+    let mut url_unmodified = GeoApiUrl::new(&base_url)?;
+
+    url_unmodified
+        .set_location(&query.name)?
+        .set_language(&language.to_string())?;
+    if !query.qualifiers.is_empty() {
+        url_unmodified.set_count(QUALIFIED_CANDIDATES)?;
+    }
+    if let Some(country_code) = &query.country_code {
+        url_unmodified.set_country(country_code)?;
+    }
+    let url = url_unmodified.url.as_str();
+
+    let ttl = Duration::from_secs(Config::get_number("cache_ttl_geolocation_seconds")?);
+    let geo_info = response_handler::deserialize(
+        client
+            .fetch_cached(url, ttl)
+            .await
+            .map(|fetched| fetched.body),
+    )?;
+
+    let matching: Vec<Location> = geo_info
+        .into_iter()
+        .filter(|location| query.matches(location))
+        .collect();
+
+    if matching.is_empty() {
+        return Err(CustomError::UnknownCity(format!(
+            "no place named '{}' in {}",
+            query.name,
+            query.qualifiers.join(", ")
+        )));
+    }
 
-    Ok(geo_info)
+    Ok(matching)
 }
 
 /// A struct representing a location using `name`, `country_code`, `timezone` and the `Coordinates` struct.
+/// The `region` (e.g. a state), the `district` (e.g. a county), the `population`, the `elevation`
+/// in meters and the GeoNames `feature_code` (e.g. `PPLC` for a capital) are only known for some places.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Location {
     pub name: String,
     pub country_code: String,
     pub timezone: String,
     pub coordinates: Coordinates,
-    pub region: String,
+    pub region: Option<String>,
+    pub district: Option<String>,
     pub country: String,
+    pub population: Option<u64>,
+    pub elevation: Option<f64>,
+    pub feature_code: Option<String>,
+}
+
+impl Location {
+    /// Instantiate a `Location` for coordinates given by the user, named after the coordinates.
+    /// Everything else about the place is unknown.
+    pub fn from_coordinates(coordinates: Coordinates) -> Self {
+        Location {
+            name: coordinates.to_string(),
+            country_code: String::new(),
+            timezone: String::new(),
+            coordinates,
+            region: None,
+            district: None,
+            country: String::new(),
+            population: None,
+            elevation: None,
+            feature_code: None,
+        }
+    }
+
+    /// Returns the region and country of the place, e.g. "Hesse, Germany", leaving out
+    /// what is unknown. Returns `None` if neither is known.
+    pub fn area(&self) -> Option<String> {
+        let parts: Vec<&str> = [self.region.as_deref().unwrap_or_default(), &self.country]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect();
+
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// Returns the name used in titles, e.g. "New York, US".
+    pub fn title(&self) -> String {
+        if self.country_code.is_empty() {
+            return self.name.clone();
+        }
+
+        format!("{}, {}", self.name, self.country_code)
+    }
 }
 
 /// A struct representing the `url` of the geolocation API.
-/// Placeholders in the `url` get replaced using setters.
+/// Query parameters of the `url` get set using setters, which percent-encode their values.
 pub struct GeoApiUrl {
-    pub url: String,
+    pub url: Url,
 }
 
 impl GeoApiUrl {
-    /// Instantiate a new `GeoApiUrl` instance.
-    pub fn new(base_url: String) -> Self {
-        GeoApiUrl { url: base_url }
+    /// Instantiate a new `GeoApiUrl` instance from the configured URL.
+    ///
+    /// # Errors
+    /// * `base_url` is not a valid absolute URL.
+    pub fn new(base_url: &str) -> Result<Self, CustomError> {
+        Ok(GeoApiUrl {
+            url: parse_url(base_url)?,
+        })
     }
 
-    /// Replaces the city name placeholder in the Geolocation API URL with a value.
-    pub fn set_location(
-        &mut self,
-        location: &str,
-    ) -> Result<&mut GeoApiUrl, Box<dyn std::error::Error>> {
-        self.url = self.url.replace("__NAME__", location);
+    /// Sets the city name searched for in the Geolocation API URL.
+    pub fn set_location(&mut self, location: &str) -> Result<&mut GeoApiUrl, CustomError> {
+        set_param(&mut self.url, "name", location);
+
+        Ok(self)
+    }
+
+    /// Sets the maximum number of results returned by the Geolocation API.
+    pub fn set_count(&mut self, count: u8) -> Result<&mut GeoApiUrl, CustomError> {
+        set_param(&mut self.url, "count", &count.to_string());
+
+        Ok(self)
+    }
+
+    /// Sets the language of the names returned by the Geolocation API, e.g. `fr`.
+    pub fn set_language(&mut self, language: &str) -> Result<&mut GeoApiUrl, CustomError> {
+        set_param(&mut self.url, "language", language);
+
+        Ok(self)
+    }
+
+    /// Restricts the results of the Geolocation API to a country, given as ISO 3166-1 alpha-2 code.
+    pub fn set_country(&mut self, country_code: &str) -> Result<&mut GeoApiUrl, CustomError> {
+        set_param(&mut self.url, "countryCode", country_code);
 
         Ok(self)
     }
@@ -69,27 +325,124 @@ impl GeoApiUrl {
 
 #[cfg(test)]
 mod tests {
-    use super::{get_info_for, Config, GeoApiUrl};
+    use super::{
+        get_info_for, parse_coordinates, parse_latitude, Config, Coordinates, Gazetteer, GeoApiUrl,
+        Language, Location, LocationQuery,
+    };
+    use crate::client::{Client, ClientSettings};
 
     #[test]
     fn setters_insert_correct_information_into_url() {
-        let mut geo_api_url = GeoApiUrl::new(Config::get_value("geo_api_url").unwrap());
+        let mut geo_api_url = GeoApiUrl::new(&Config::get_value("geo_api_url").unwrap()).unwrap();
+
+        let actual_url = geo_api_url
+            .set_location("New York")
+            .unwrap()
+            .set_count(5)
+            .unwrap()
+            .set_language("fr")
+            .unwrap()
+            .set_country("US")
+            .unwrap()
+            .url
+            .as_str();
+
+        assert!(actual_url.contains("count=5&language=fr"));
+        assert!(actual_url.ends_with("&name=New+York&countryCode=US"));
+    }
+
+    #[test]
+    fn set_location_encodes_special_characters() {
+        let mut geo_api_url = GeoApiUrl::new(&Config::get_value("geo_api_url").unwrap()).unwrap();
 
-        let actual_url = &geo_api_url.set_location("New York").unwrap().url;
+        let actual_url = geo_api_url
+            .set_location("Bar & Grill, TX")
+            .unwrap()
+            .url
+            .as_str();
 
-        assert!(actual_url.contains("name=New York"));
+        assert!(actual_url.ends_with("&name=Bar+%26+Grill%2C+TX"));
+
+        let actual_url = geo_api_url.set_location("Şanlıurfa").unwrap().url.as_str();
+
+        assert!(actual_url.ends_with("&name=%C5%9Eanl%C4%B1urfa"));
     }
 
     #[tokio::test]
     async fn get_info_for_fetches_required_geolocation_information() {
-        let result = get_info_for("New York").await.unwrap();
+        // Don't write into the user's cache directory.
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.cache_dir = None;
+        let client = Client::new(settings).unwrap();
+
+        let results = get_info_for(
+            &client,
+            &LocationQuery::parse("New York", None),
+            &Language::En,
+            &Gazetteer::Off,
+        )
+        .await
+        .unwrap();
+        let result = &results[0];
 
         assert_eq!(result.name, "New York");
         assert_eq!(result.country_code, "US");
         assert_eq!(result.timezone, "America/New_York");
-        assert_eq!(result.coordinates.latitude, "40.71427");
-        assert_eq!(result.coordinates.longitude, "-74.00597");
-        assert_eq!(result.region, "New York");
+        assert_eq!(result.coordinates.latitude(), 40.71427);
+        assert_eq!(result.coordinates.longitude(), -74.00597);
+        assert_eq!(result.region.as_deref(), Some("New York"));
         assert_eq!(result.country, "United States");
     }
+
+    #[test]
+    fn area_leaves_out_unknown_parts() {
+        let mut location = Location::from_coordinates(Coordinates::new(-77.846, 166.676).unwrap());
+        assert_eq!(location.area(), None);
+
+        location.country = "Antarctica".to_string();
+        assert_eq!(location.area().as_deref(), Some("Antarctica"));
+
+        location.region = Some("Ross Dependency".to_string());
+        assert_eq!(
+            location.area().as_deref(),
+            Some("Ross Dependency, Antarctica")
+        );
+    }
+
+    #[test]
+    fn parse_coordinates_accepts_decimal_degrees_in_range() {
+        let coordinates = parse_coordinates("52.52, -13.41").unwrap();
+
+        assert_eq!(coordinates.latitude(), 52.52);
+        assert_eq!(coordinates.longitude(), -13.41);
+        assert!(parse_coordinates("52.52").is_err());
+        assert!(parse_coordinates("91,13.41").is_err());
+        assert!(parse_coordinates("52.52,180.5").is_err());
+        assert!(parse_latitude("north").is_err());
+        assert!(Coordinates::new(f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn coordinates_are_checked_when_read() {
+        let coordinates: Coordinates =
+            serde_json::from_str(r#"{"latitude":50.11552,"longitude":8.68417}"#).unwrap();
+
+        assert_eq!(coordinates, Coordinates::new(50.11552, 8.68417).unwrap());
+        assert_eq!(
+            serde_json::to_string(&coordinates).unwrap(),
+            r#"{"latitude":50.11552,"longitude":8.68417}"#
+        );
+        assert!(serde_json::from_str::<Coordinates>(r#"{"latitude":95,"longitude":0}"#).is_err());
+        assert!(
+            serde_json::from_str::<Coordinates>(r#"{"latitude":"50.1","longitude":"8.6"}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn location_from_coordinates_is_titled_by_coordinates() {
+        let location = Location::from_coordinates(Coordinates::new(52.52, 13.41).unwrap());
+
+        assert_eq!(location.title(), "52.52, 13.41");
+    }
 }
@@ -0,0 +1,191 @@
+use super::Location;
+
+/// US states and Canadian provinces by their postal abbreviation, as used in "Portland, OR".
+const REGION_CODES: [(&str, &str, &str); 64] = [
+    ("US", "AL", "Alabama"),
+    ("US", "AK", "Alaska"),
+    ("US", "AZ", "Arizona"),
+    ("US", "AR", "Arkansas"),
+    ("US", "CA", "California"),
+    ("US", "CO", "Colorado"),
+    ("US", "CT", "Connecticut"),
+    ("US", "DE", "Delaware"),
+    ("US", "DC", "Washington, D.C."),
+    ("US", "FL", "Florida"),
+    ("US", "GA", "Georgia"),
+    ("US", "HI", "Hawaii"),
+    ("US", "ID", "Idaho"),
+    ("US", "IL", "Illinois"),
+    ("US", "IN", "Indiana"),
+    ("US", "IA", "Iowa"),
+    ("US", "KS", "Kansas"),
+    ("US", "KY", "Kentucky"),
+    ("US", "LA", "Louisiana"),
+    ("US", "ME", "Maine"),
+    ("US", "MD", "Maryland"),
+    ("US", "MA", "Massachusetts"),
+    ("US", "MI", "Michigan"),
+    ("US", "MN", "Minnesota"),
+    ("US", "MS", "Mississippi"),
+    ("US", "MO", "Missouri"),
+    ("US", "MT", "Montana"),
+    ("US", "NE", "Nebraska"),
+    ("US", "NV", "Nevada"),
+    ("US", "NH", "New Hampshire"),
+    ("US", "NJ", "New Jersey"),
+    ("US", "NM", "New Mexico"),
+    ("US", "NY", "New York"),
+    ("US", "NC", "North Carolina"),
+    ("US", "ND", "North Dakota"),
+    ("US", "OH", "Ohio"),
+    ("US", "OK", "Oklahoma"),
+    ("US", "OR", "Oregon"),
+    ("US", "PA", "Pennsylvania"),
+    ("US", "RI", "Rhode Island"),
+    ("US", "SC", "South Carolina"),
+    ("US", "SD", "South Dakota"),
+    ("US", "TN", "Tennessee"),
+    ("US", "TX", "Texas"),
+    ("US", "UT", "Utah"),
+    ("US", "VT", "Vermont"),
+    ("US", "VA", "Virginia"),
+    ("US", "WA", "Washington"),
+    ("US", "WV", "West Virginia"),
+    ("US", "WI", "Wisconsin"),
+    ("US", "WY", "Wyoming"),
+    ("CA", "AB", "Alberta"),
+    ("CA", "BC", "British Columbia"),
+    ("CA", "MB", "Manitoba"),
+    ("CA", "NB", "New Brunswick"),
+    ("CA", "NL", "Newfoundland and Labrador"),
+    ("CA", "NS", "Nova Scotia"),
+    ("CA", "NT", "Northwest Territories"),
+    ("CA", "NU", "Nunavut"),
+    ("CA", "ON", "Ontario"),
+    ("CA", "PE", "Prince Edward Island"),
+    ("CA", "QC", "Quebec"),
+    ("CA", "SK", "Saskatchewan"),
+    ("CA", "YT", "Yukon"),
+];
+
+/// A struct representing a location query like "Portland, OR, US" using the searched `name`
+/// and the `qualifiers` a matching place must have, e.g. its region, country or country code.
+pub struct LocationQuery {
+    pub name: String,
+    pub qualifiers: Vec<String>,
+    pub country_code: Option<String>,
+}
+
+impl LocationQuery {
+    /// Splits a query at its commas into the name and its qualifiers.
+    ///
+    /// # Arguments
+    /// * `query`: The query as given, e.g. "Paris, FR".
+    /// * `country`: A country every matching place must be in, given as name or ISO 3166-1
+    ///   alpha-2 code. A code is also passed on to the geolocation API.
+    pub fn parse(query: &str, country: Option<&str>) -> Self {
+        let mut parts = query
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty());
+
+        let name = parts.next().unwrap_or_default().to_string();
+        let mut qualifiers: Vec<String> = parts.map(str::to_string).collect();
+
+        let country = country.map(str::trim).filter(|country| !country.is_empty());
+        let country_code = country
+            .filter(|country| country.len() == 2 && country.chars().all(char::is_alphabetic))
+            .map(str::to_uppercase);
+        qualifiers.extend(country.map(str::to_string));
+
+        LocationQuery {
+            name,
+            qualifiers,
+            country_code,
+        }
+    }
+
+    /// Returns whether `location` has all qualifiers of the query.
+    pub fn matches(&self, location: &Location) -> bool {
+        self.qualifiers
+            .iter()
+            .all(|qualifier| has_qualifier(location, qualifier))
+    }
+}
+
+/// Checks whether `qualifier` is the region, its postal abbreviation, the district, the country
+/// or the country code.
+fn has_qualifier(location: &Location, qualifier: &str) -> bool {
+    let qualifier = qualifier.to_lowercase();
+    let is = |value: &str| value.to_lowercase() == qualifier;
+
+    location.region.as_deref().is_some_and(is)
+        || location.district.as_deref().is_some_and(is)
+        || is(&location.country)
+        || is(&location.country_code)
+        || REGION_CODES.iter().any(|(country_code, code, region)| {
+            is(code)
+                && location.country_code.eq_ignore_ascii_case(country_code)
+                && location.region.as_deref() == Some(*region)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocationQuery;
+    use crate::geolocation::{Coordinates, Location};
+
+    fn place(name: &str, region: &str, country_code: &str, country: &str) -> Location {
+        Location {
+            name: name.to_string(),
+            country_code: country_code.to_string(),
+            timezone: String::new(),
+            coordinates: Coordinates::new(0.0, 0.0).unwrap(),
+            region: Some(region.to_string()),
+            district: None,
+            country: country.to_string(),
+            population: None,
+            elevation: None,
+            feature_code: None,
+        }
+    }
+
+    #[test]
+    fn parse_splits_name_and_qualifiers() {
+        let query = LocationQuery::parse(" Portland,  OR ,US, ", None);
+
+        assert_eq!(query.name, "Portland");
+        assert_eq!(query.qualifiers, vec!["OR", "US"]);
+        assert_eq!(query.country_code, None);
+    }
+
+    #[test]
+    fn parse_adds_country_as_qualifier_and_code() {
+        let query = LocationQuery::parse("Paris", Some("fr"));
+
+        assert_eq!(query.qualifiers, vec!["fr"]);
+        assert_eq!(query.country_code.as_deref(), Some("FR"));
+
+        let query = LocationQuery::parse("Paris", Some("France"));
+
+        assert_eq!(query.qualifiers, vec!["France"]);
+        assert_eq!(query.country_code, None);
+    }
+
+    #[test]
+    fn matches_region_abbreviation_country_and_code() {
+        let oregon = place("Portland", "Oregon", "US", "United States");
+        let maine = place("Portland", "Maine", "US", "United States");
+        let texas = place("Paris", "Texas", "US", "United States");
+        let france = place("Paris", "Île-de-France", "FR", "France");
+
+        assert!(LocationQuery::parse("Portland, OR, US", None).matches(&oregon));
+        assert!(!LocationQuery::parse("Portland, OR, US", None).matches(&maine));
+        assert!(LocationQuery::parse("Portland, maine", None).matches(&maine));
+        assert!(LocationQuery::parse("Paris, FR", None).matches(&france));
+        assert!(!LocationQuery::parse("Paris, FR", None).matches(&texas));
+        assert!(LocationQuery::parse("Paris, TX", None).matches(&texas));
+        assert!(LocationQuery::parse("Paris, île-de-france", None).matches(&france));
+        assert!(LocationQuery::parse("Paris", Some("United States")).matches(&texas));
+    }
+}
@@ -0,0 +1,202 @@
+use super::geo_api::response_handler;
+use super::{Coordinates, Location};
+use crate::client::query::{parse_url, set_param};
+use crate::client::Client;
+use crate::config::args::Language;
+use crate::config::Config;
+use crate::errors::CustomError;
+use reqwest::Url;
+use std::fmt::Display;
+use std::time::Duration;
+
+/// A service naming the place at a pair of coordinates.
+pub trait ReverseGeocoder {
+    /// Looks up the nearest named place at `coordinates`.
+    ///
+    /// # Errors
+    /// * No place is known at the coordinates.
+    /// * The lookup itself fails, e.g. because the service cannot be reached.
+    async fn locate(&self, coordinates: &Coordinates) -> Result<Location, CustomError>;
+}
+
+/// The reverse geocoding backends that can be configured with `reverse_geocoder`.
+#[derive(Clone, PartialEq, Debug, clap::ValueEnum)]
+pub enum Backend {
+    /// OpenStreetMap's Nominatim, or a compatible service at `reverse_geo_api_url`.
+    Nominatim,
+    /// Don't name coordinates.
+    None,
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Backend::Nominatim => "nominatim".to_string(),
+            Backend::None => "none".to_string(),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// A `ReverseGeocoder` using the Nominatim API, see <https://nominatim.org/release-docs/latest/api/Reverse/>.
+pub struct Nominatim<'a> {
+    client: &'a Client,
+    language: &'a Language,
+}
+
+impl<'a> Nominatim<'a> {
+    /// Instantiate a new `Nominatim` geocoder sending its requests with `client`,
+    /// asking for place names in `language`.
+    pub fn new(client: &'a Client, language: &'a Language) -> Self {
+        Nominatim { client, language }
+    }
+}
+
+impl ReverseGeocoder for Nominatim<'_> {
+    async fn locate(&self, coordinates: &Coordinates) -> Result<Location, CustomError> {
+        let base_url = Config::get_api_url("reverse_geo_api_url")?;
+        let mut url_unmodified = ReverseGeoApiUrl::new(&base_url)?;
+
+        let url = url_unmodified
+            .set_coordinates(coordinates)?
+            .set_language(&self.language.to_string())?
+            .url
+            .as_str();
+
+        let ttl = Duration::from_secs(Config::get_number("cache_ttl_geolocation_seconds")?);
+        response_handler::deserialize_reverse(
+            self.client
+                .fetch_cached(url, ttl)
+                .await
+                .map(|fetched| fetched.body),
+        )
+    }
+}
+
+/// Names the place at `coordinates` using `geocoder`.
+///
+/// The returned `Location` keeps the given coordinates, as the weather is shown for them rather
+/// than for the center of the named place. If the lookup fails, the place is named after its
+/// coordinates instead, since the weather can be shown either way.
+pub async fn name(geocoder: &impl ReverseGeocoder, coordinates: Coordinates) -> Location {
+    match geocoder.locate(&coordinates).await {
+        Ok(location) => Location {
+            coordinates,
+            ..location
+        },
+        Err(_) => Location::from_coordinates(coordinates),
+    }
+}
+
+/// A struct representing the `url` of the reverse geocoding API.
+/// Query parameters of the `url` get set using setters, which percent-encode their values.
+pub struct ReverseGeoApiUrl {
+    pub url: Url,
+}
+
+impl ReverseGeoApiUrl {
+    /// Instantiate a new `ReverseGeoApiUrl` instance from the configured URL.
+    ///
+    /// # Errors
+    /// * `base_url` is not a valid absolute URL.
+    pub fn new(base_url: &str) -> Result<Self, CustomError> {
+        Ok(ReverseGeoApiUrl {
+            url: parse_url(base_url)?,
+        })
+    }
+
+    /// Sets the coordinates in the reverse geocoding API URL.
+    pub fn set_coordinates(
+        &mut self,
+        coordinates: &Coordinates,
+    ) -> Result<&mut ReverseGeoApiUrl, CustomError> {
+        set_param(&mut self.url, "lat", &coordinates.latitude().to_string());
+        set_param(&mut self.url, "lon", &coordinates.longitude().to_string());
+
+        Ok(self)
+    }
+
+    /// Sets the preferred language of the place names in the reverse geocoding API URL, e.g. `de`.
+    pub fn set_language(&mut self, language: &str) -> Result<&mut ReverseGeoApiUrl, CustomError> {
+        set_param(&mut self.url, "accept-language", language);
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{name, ReverseGeoApiUrl, ReverseGeocoder};
+    use crate::config::Config;
+    use crate::errors::CustomError;
+    use crate::geolocation::{Coordinates, Location};
+
+    /// A stand-in geocoder knowing a single place.
+    struct Fixed(Option<&'static str>);
+
+    impl ReverseGeocoder for Fixed {
+        async fn locate(&self, coordinates: &Coordinates) -> Result<Location, CustomError> {
+            let name = self
+                .0
+                .ok_or_else(|| CustomError::UnknownCity("nothing here".to_string()))?;
+
+            Ok(Location {
+                name: name.to_string(),
+                country_code: "DE".to_string(),
+                timezone: String::new(),
+                coordinates: Coordinates::new(
+                    coordinates.latitude().round(),
+                    coordinates.longitude().round(),
+                )
+                .unwrap(),
+                region: Some("Berlin".to_string()),
+                district: None,
+                country: "Germany".to_string(),
+                population: None,
+                elevation: None,
+                feature_code: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn name_keeps_given_coordinates() {
+        let location = name(
+            &Fixed(Some("Berlin")),
+            Coordinates::new(52.52, 13.41).unwrap(),
+        )
+        .await;
+
+        assert_eq!(location.title(), "Berlin, DE");
+        assert_eq!(location.region.as_deref(), Some("Berlin"));
+        assert_eq!(
+            location.coordinates,
+            Coordinates::new(52.52, 13.41).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn name_falls_back_to_coordinates() {
+        let location = name(&Fixed(None), Coordinates::new(0.5, -20.25).unwrap()).await;
+
+        assert_eq!(location.title(), "0.5, -20.25");
+        assert!(location.country.is_empty());
+    }
+
+    #[test]
+    fn set_coordinates_inserts_coordinates_into_url() {
+        let mut url =
+            ReverseGeoApiUrl::new(&Config::get_value("reverse_geo_api_url").unwrap()).unwrap();
+
+        let actual_url = url
+            .set_coordinates(&Coordinates::new(52.52, 13.41).unwrap())
+            .unwrap()
+            .set_language("ja")
+            .unwrap()
+            .url
+            .as_str();
+
+        assert!(actual_url.contains("accept-language=ja"));
+        assert!(actual_url.ends_with("&lat=52.52&lon=13.41"));
+    }
+}
@@ -0,0 +1,290 @@
+use super::Location;
+use crate::errors::CustomError;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// How a place is chosen when a city name matches several places.
+pub enum Selection {
+    /// Take the place at this 1-based position of the list of matches.
+    Pick(u8),
+    /// Show the matches and ask the user on the terminal.
+    Prompt,
+    /// Fail with the list of matches.
+    Fail,
+}
+
+impl Selection {
+    /// Uses `pick` if given, otherwise prompts if both stdin and stderr are a terminal.
+    pub fn new(pick: Option<u8>) -> Self {
+        match pick {
+            Some(pick) => Selection::Pick(pick),
+            None if io::stdin().is_terminal() && io::stderr().is_terminal() => Selection::Prompt,
+            None => Selection::Fail,
+        }
+    }
+}
+
+/// Chooses the place meant by `query` from the `candidates` returned by the geolocation API.
+///
+/// Candidates whose name starts with the query are plausible matches. If there is only one,
+/// or none at all, it or the first candidate is used. Otherwise `selection` decides.
+///
+/// # Errors
+/// * Several places match and `selection` is `Fail`.
+/// * The picked position is not in the list of matches.
+/// * The prompt cannot be answered, e.g. because stdin was closed.
+pub fn choose(
+    query: &str,
+    candidates: Vec<Location>,
+    selection: &Selection,
+) -> Result<Location, CustomError> {
+    let mut matches = plausible_matches(query, candidates);
+
+    if matches.len() == 1 {
+        return Ok(matches.remove(0));
+    }
+
+    let position = match selection {
+        Selection::Pick(pick) => *pick as usize,
+        Selection::Prompt => prompt(query, &matches, &mut io::stdin().lock(), &mut io::stderr())?,
+        Selection::Fail => {
+            return Err(CustomError::AmbiguousCity(format!(
+                "\n{}",
+                numbered_list(&matches)
+            )))
+        }
+    };
+
+    if position == 0 || position > matches.len() {
+        return Err(CustomError::AmbiguousCity(format!(
+            "cannot pick {} of {} places\n{}",
+            position,
+            matches.len(),
+            numbered_list(&matches)
+        )));
+    }
+
+    Ok(matches.remove(position - 1))
+}
+
+/// Keeps the candidates whose name starts with the word(s) of `query`, ignoring case,
+/// e.g. "Frankfurt am Main" and "Frankfurt (Oder)" for "frankfurt", but not "Frankfurter Berg".
+/// Keeps only the first candidate if none does, as the API also matches e.g. alternative names.
+fn plausible_matches(query: &str, mut candidates: Vec<Location>) -> Vec<Location> {
+    let query = query.trim().to_lowercase();
+    let is_match = |candidate: &Location| {
+        candidate
+            .name
+            .to_lowercase()
+            .strip_prefix(&query)
+            .is_some_and(|rest| !rest.starts_with(char::is_alphanumeric))
+    };
+
+    if !candidates.iter().any(is_match) {
+        candidates.truncate(1);
+        return candidates;
+    }
+
+    candidates.into_iter().filter(is_match).collect()
+}
+
+/// Shows the numbered `matches` on `output` and reads the chosen position from `input`,
+/// asking again until a valid position is entered.
+fn prompt(
+    query: &str,
+    matches: &[Location],
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<usize, CustomError> {
+    let unanswered = |reason: &str| {
+        CustomError::AmbiguousCity(format!("{}\n{}", reason, numbered_list(matches)))
+    };
+
+    writeln!(output, "\"{}\" matches several places:", query)
+        .and_then(|_| writeln!(output, "{}", numbered_list(matches)))
+        .map_err(|err| unanswered(&err.to_string()))?;
+
+    loop {
+        write!(output, "Choose a place [1-{}]: ", matches.len())
+            .and_then(|_| output.flush())
+            .map_err(|err| unanswered(&err.to_string()))?;
+
+        let mut answer = String::new();
+        match input.read_line(&mut answer) {
+            Ok(0) => return Err(unanswered("no place chosen")),
+            Ok(_) => {}
+            Err(err) => return Err(unanswered(&err.to_string())),
+        }
+
+        match answer.trim().parse::<usize>() {
+            Ok(position) if (1..=matches.len()).contains(&position) => return Ok(position),
+            _ => {
+                let _ = writeln!(output, "Please enter a number from 1 to {}.", matches.len());
+            }
+        }
+    }
+}
+
+/// Lists the `matches` one per line, numbered from 1.
+fn numbered_list(matches: &[Location]) -> String {
+    matches
+        .iter()
+        .enumerate()
+        .map(|(index, location)| format!("  {}) {}", index + 1, describe(location)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Describes a place with everything needed to tell it apart from others of the same name,
+/// e.g. "Frankfurt (Oder), Brandenburg, Germany - population 57,751 - 52.34714, 14.55062".
+/// The district is included if known, e.g. to tell apart places of the same name in one state.
+fn describe(location: &Location) -> String {
+    let population = match location.population {
+        Some(population) => format!(" - population {}", thousands(population)),
+        None => String::new(),
+    };
+
+    let place = [
+        Some(location.name.as_str()),
+        location.district.as_deref(),
+        location.region.as_deref(),
+        Some(location.country.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<&str>>()
+    .join(", ");
+
+    format!("{}{} - {}", place, population, location.coordinates)
+}
+
+/// Formats a number with commas between groups of thousands.
+fn thousands(number: u64) -> String {
+    let digits = number.to_string();
+    let mut formatted = String::new();
+
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choose, describe, prompt, thousands, Selection};
+    use crate::errors::CustomError;
+    use crate::geolocation::{Coordinates, Location};
+
+    fn place(name: &str, region: &str, population: Option<u64>) -> Location {
+        Location {
+            name: name.to_string(),
+            country_code: "DE".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+            coordinates: Coordinates::new(52.34714, 14.55062).unwrap(),
+            region: Some(region.to_string()),
+            district: None,
+            country: "Germany".to_string(),
+            population,
+            elevation: None,
+            feature_code: None,
+        }
+    }
+
+    fn frankfurts() -> Vec<Location> {
+        vec![
+            place("Frankfurt (Oder)", "Brandenburg", Some(57751)),
+            place("Frankfurt am Main", "Hesse", Some(650000)),
+            place("Neu-Frankfurt", "Hesse", None),
+        ]
+    }
+
+    #[test]
+    fn choose_uses_single_plausible_match() {
+        let candidates = vec![
+            place("Berlin", "Land Berlin", None),
+            place("Berlingen", "Thurgau", None),
+        ];
+
+        let result = choose("berlin ", candidates, &Selection::Fail);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn choose_uses_first_candidate_without_plausible_match() {
+        let candidates = vec![place("New York", "New York", None)];
+
+        let result = choose("NYC", candidates, &Selection::Fail).unwrap();
+
+        assert_eq!(result.name, "New York");
+    }
+
+    #[test]
+    fn choose_fails_with_candidate_list_when_ambiguous() {
+        let result = choose("Frankfurt", frankfurts(), &Selection::Fail);
+
+        assert!(matches!(&result, Err(CustomError::AmbiguousCity(_))));
+        let message = result.err().unwrap().to_string();
+        assert!(message.contains("1) Frankfurt (Oder), Brandenburg, Germany"));
+        assert!(message.contains("2) Frankfurt am Main, Hesse, Germany"));
+        assert!(!message.contains("Neu-Frankfurt"));
+    }
+
+    #[test]
+    fn choose_picks_given_position() {
+        let result = choose("Frankfurt", frankfurts(), &Selection::Pick(2)).unwrap();
+
+        assert_eq!(result.name, "Frankfurt am Main");
+        assert!(matches!(
+            choose("Frankfurt", frankfurts(), &Selection::Pick(3)),
+            Err(CustomError::AmbiguousCity(_))
+        ));
+    }
+
+    #[test]
+    fn prompt_asks_until_valid_position() {
+        let matches = &frankfurts()[..2];
+        let mut output = Vec::new();
+
+        let position = prompt(
+            "Frankfurt",
+            matches,
+            &mut "x\n7\n2\n".as_bytes(),
+            &mut output,
+        );
+
+        assert_eq!(position.unwrap(), 2);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("\"Frankfurt\" matches several places"));
+        assert_eq!(
+            output.matches("Please enter a number from 1 to 2.").count(),
+            2
+        );
+    }
+
+    #[test]
+    fn prompt_fails_on_closed_input() {
+        let result = prompt(
+            "Frankfurt",
+            &frankfurts(),
+            &mut "".as_bytes(),
+            &mut Vec::new(),
+        );
+
+        assert!(matches!(result, Err(CustomError::AmbiguousCity(_))));
+    }
+
+    #[test]
+    fn describe_includes_region_population_and_coordinates() {
+        assert_eq!(
+            describe(&place("Frankfurt (Oder)", "Brandenburg", Some(57751))),
+            "Frankfurt (Oder), Brandenburg, Germany - population 57,751 - 52.34714, 14.55062"
+        );
+        assert_eq!(thousands(650), "650");
+        assert_eq!(thousands(1234567), "1,234,567");
+    }
+}
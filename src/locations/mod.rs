@@ -0,0 +1,252 @@
+use crate::errors::CustomError;
+use crate::geolocation::Location;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// The alias used when no city is given.
+pub const DEFAULT_ALIAS: &str = "default";
+
+/// The places saved by the user under an alias, e.g. `office`, stored as JSON in `path`.
+pub struct SavedLocations {
+    path: PathBuf,
+    locations: BTreeMap<String, Location>,
+}
+
+impl SavedLocations {
+    /// Returns the default location of the store, e.g. `$XDG_DATA_HOME/weather-wand/locations.json` on Linux.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("weather-wand").join("locations.json"))
+    }
+
+    /// Reads the saved locations from `path`. A missing file is an empty store.
+    ///
+    /// # Errors
+    /// * The file cannot be read or is not a valid store.
+    pub fn load(path: PathBuf) -> Result<Self, CustomError> {
+        let locations = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|err| {
+                CustomError::SavedLocations(format!("{}: {}", path.display(), err))
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                return Err(CustomError::SavedLocations(format!(
+                    "{}: {}",
+                    path.display(),
+                    err
+                )))
+            }
+        };
+
+        Ok(SavedLocations { path, locations })
+    }
+
+    /// Reads the saved locations from the default path, or returns an empty store
+    /// if the platform has no data directory.
+    ///
+    /// # Errors
+    /// * The file cannot be read or is not a valid store.
+    pub fn load_default() -> Result<Self, CustomError> {
+        match Self::default_path() {
+            Some(path) => Self::load(path),
+            None => Ok(SavedLocations {
+                path: PathBuf::new(),
+                locations: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Returns the place saved under `alias`, ignoring case.
+    pub fn get(&self, alias: &str) -> Option<&Location> {
+        self.locations.get(&alias.trim().to_lowercase())
+    }
+
+    /// Returns the saved places ordered by their alias.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Location)> {
+        self.locations.iter()
+    }
+
+    /// Saves `location` under `alias`, replacing a place saved under the same alias.
+    ///
+    /// # Returns
+    /// Returns the replaced place, if any.
+    ///
+    /// # Errors
+    /// * `alias` is empty or contains a comma, which would make it look like a qualified city.
+    /// * The store cannot be written.
+    pub fn add(
+        &mut self,
+        alias: &str,
+        location: Location,
+    ) -> Result<Option<Location>, CustomError> {
+        let alias = check_alias(alias)?;
+        let replaced = self.locations.insert(alias, location);
+        self.save()?;
+
+        Ok(replaced)
+    }
+
+    /// Removes the place saved under `alias`.
+    ///
+    /// # Errors
+    /// * No place is saved under `alias`.
+    /// * The store cannot be written.
+    pub fn remove(&mut self, alias: &str) -> Result<Location, CustomError> {
+        let location = self
+            .locations
+            .remove(&alias.trim().to_lowercase())
+            .ok_or_else(|| not_saved(alias))?;
+        self.save()?;
+
+        Ok(location)
+    }
+
+    /// Moves the place saved under `alias` to `new_alias`.
+    ///
+    /// # Errors
+    /// * No place is saved under `alias`.
+    /// * `new_alias` is invalid or already in use.
+    /// * The store cannot be written.
+    pub fn rename(&mut self, alias: &str, new_alias: &str) -> Result<(), CustomError> {
+        let new_alias = check_alias(new_alias)?;
+        if self.locations.contains_key(&new_alias) {
+            return Err(CustomError::SavedLocations(format!(
+                "'{}' is already in use, remove it first",
+                new_alias
+            )));
+        }
+
+        let location = self
+            .locations
+            .remove(&alias.trim().to_lowercase())
+            .ok_or_else(|| not_saved(alias))?;
+        self.locations.insert(new_alias, location);
+
+        self.save()
+    }
+
+    /// Writes the store to its file, creating its directory if needed.
+    fn save(&self) -> Result<(), CustomError> {
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            // Write to a temporary file first, so a failed write never loses the saved locations.
+            let temporary_path = self.path.with_extension("tmp");
+            fs::write(
+                &temporary_path,
+                serde_json::to_string_pretty(&self.locations)?,
+            )?;
+            fs::rename(temporary_path, &self.path)
+        };
+
+        if self.path.as_os_str().is_empty() {
+            return Err(CustomError::SavedLocations(
+                "no data directory found to save locations in".to_string(),
+            ));
+        }
+
+        write()
+            .map_err(|err| CustomError::SavedLocations(format!("{}: {}", self.path.display(), err)))
+    }
+}
+
+/// Normalizes an alias to lowercase, rejecting empty aliases and aliases containing commas.
+fn check_alias(alias: &str) -> Result<String, CustomError> {
+    let alias = alias.trim().to_lowercase();
+
+    if alias.is_empty() || alias.contains(',') {
+        return Err(CustomError::SavedLocations(format!(
+            "'{}' is not a valid alias, it must not be empty or contain commas",
+            alias
+        )));
+    }
+
+    Ok(alias)
+}
+
+fn not_saved(alias: &str) -> CustomError {
+    CustomError::SavedLocations(format!("no location saved as '{}'", alias.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SavedLocations;
+    use crate::errors::CustomError;
+    use crate::geolocation::{Coordinates, Location};
+
+    fn frankfurt() -> Location {
+        Location {
+            name: "Frankfurt am Main".to_string(),
+            country_code: "DE".to_string(),
+            timezone: "Europe/Berlin".to_string(),
+            coordinates: Coordinates::new(50.11552, 8.68417).unwrap(),
+            region: Some("Hesse".to_string()),
+            district: None,
+            country: "Germany".to_string(),
+            population: Some(650000),
+            elevation: Some(112.0),
+            feature_code: Some("PPLA2".to_string()),
+        }
+    }
+
+    #[test]
+    fn add_persists_location_under_lowercase_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("weather-wand").join("locations.json");
+
+        let mut store = SavedLocations::load(path.clone()).unwrap();
+        assert!(store.add("Office", frankfurt()).unwrap().is_none());
+
+        let store = SavedLocations::load(path).unwrap();
+        let office = store.get("OFFICE").unwrap();
+        assert_eq!(office.title(), "Frankfurt am Main, DE");
+        assert_eq!(office.timezone, "Europe/Berlin");
+        assert_eq!(office.coordinates.latitude(), 50.11552);
+        assert!(store.get("home").is_none());
+    }
+
+    #[test]
+    fn rename_and_remove_update_the_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locations.json");
+
+        let mut store = SavedLocations::load(path.clone()).unwrap();
+        store.add("office", frankfurt()).unwrap();
+        store.add("home", frankfurt()).unwrap();
+
+        assert!(matches!(
+            store.rename("office", "home"),
+            Err(CustomError::SavedLocations(_))
+        ));
+        store.rename("office", "work").unwrap();
+        store.remove("home").unwrap();
+
+        let store = SavedLocations::load(path).unwrap();
+        let aliases: Vec<&String> = store.iter().map(|(alias, _)| alias).collect();
+        assert_eq!(aliases, vec!["work"]);
+        assert!(matches!(
+            SavedLocations::load(dir.path().join("locations.json"))
+                .unwrap()
+                .remove("office"),
+            Err(CustomError::SavedLocations(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_aliases_and_files_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locations.json");
+
+        let mut store = SavedLocations::load(path.clone()).unwrap();
+        assert!(store.add("Paris, FR", frankfurt()).is_err());
+        assert!(store.add(" ", frankfurt()).is_err());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            SavedLocations::load(path),
+            Err(CustomError::SavedLocations(_))
+        ));
+    }
+}
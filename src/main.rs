@@ -1,72 +1,401 @@
+mod cache;
+mod client;
 mod config;
 mod errors;
 mod geolocation;
+mod locations;
 mod output;
 mod weather;
 
 use clap::Parser;
-use config::args::Args;
+use client::{Client, ClientSettings};
+use config::args::{Args, Command, Language, LocationsAction, OutputFormat};
+use config::settings::{config_enum, or_config, Settings};
+use config::Config;
+use errors::CustomError;
+use futures::future::join_all;
+use geolocation::query::LocationQuery;
+use geolocation::reverse::{self, Backend, Nominatim};
+use geolocation::selection::{self, Selection};
+use geolocation::{Coordinates, Location};
+use locations::SavedLocations;
 use output::WeatherOutput;
+use std::process::ExitCode;
+use weather::{CurrentWeather, DailyForecast, HourlyForecast};
+
+/// Everything fetched for a single city, ready to be printed.
+struct CityReport {
+    geo_info: Location,
+    weather_info: CurrentWeather,
+    daily_forecast: Option<Result<DailyForecast, CustomError>>,
+    hourly_forecast: Option<Result<HourlyForecast, CustomError>>,
+}
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     let args: Args = Args::parse();
 
-    let geo_info_result = geolocation::get_info_for(&args.city[0]).await;
+    let setup = Config::load(args.config.as_deref(), args.config_values()).and_then(|_| {
+        Config::validate_api_urls()?;
+        let saved = SavedLocations::load_default()?;
+        let client = Client::new(ClientSettings::from_config()?.with_args(&args))?;
+        Ok((saved, client))
+    });
 
-    let geo_info = match geo_info_result {
-        Ok(geo_info) => geo_info,
+    let (mut saved, client) = match setup {
+        Ok(setup) => setup,
         Err(e) => {
-            let error_display = format!("{}", e);
-            // TODO: Match on error type instead of error message
-            match error_display.as_str() {
-                message if message.contains("Geolocation information not found") => {
-                    eprintln!(
-                        "Error: Required information not found in the geolocation data \n{}\nPlease provide a valid city name", message
-                    );
-                }
-                _ => {
-                    eprintln!("Unexpected error type: {}", e);
-                }
+            eprintln!("Error: {}", e);
+            return ExitCode::from(e.exit_code());
+        }
+    };
+
+    if let Some(Command::Locations { action }) = &args.command {
+        return match manage_locations(&args, action, &mut saved, &client).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from(e.exit_code())
             }
-            return Ok(());
+        };
+    }
+
+    let settings = match Settings::new(&args, &saved) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(e.exit_code());
         }
     };
 
-    let temperature_unit = &args.temperature_unit;
-    let windspeed_unit = &args.windspeed_unit;
-    let clock_display = &args.display;
+    let queries: Vec<LocationQuery> = settings
+        .city
+        .iter()
+        .map(|city| LocationQuery::parse(city, settings.country.as_deref()))
+        .collect();
 
-    let weather_info_result =
-        weather::get_info_for(&geo_info.coordinates, temperature_unit, windspeed_unit).await;
+    // Saved locations are used as they are, other cities are geocoded.
+    let candidates = join_all(settings.city.iter().zip(&queries).map(|(city, query)| {
+        let (client, saved, settings) = (&client, &saved, &settings);
+        async move {
+            match saved.get(city) {
+                Some(location) => Ok(vec![location.clone()]),
+                None => {
+                    geolocation::get_info_for(
+                        client,
+                        query,
+                        &settings.language,
+                        &settings.gazetteer,
+                    )
+                    .await
+                }
+            }
+        }
+    }))
+    .await;
 
-    let weather_info = match weather_info_result {
-        Ok(weather_info) => weather_info,
-        Err(e) => {
-            let error_display = format!("{}", e);
-            // TODO: Match on error type instead of error message
-            match error_display.as_str() {
-                message if message.contains("Weather information not found") => {
-                    eprintln!(
-                        "Error: Required information not found in the weather data \n{}\nPlease provide valid arguments",
-                        message
-                    );
+    // Places are chosen one city after another, so prompts don't interleave.
+    let selection = Selection::new(settings.pick);
+    let mut locations: Vec<(String, Location)> = Vec::new();
+    let mut errors = Vec::new();
+
+    // Coordinates given by the user don't need to be geocoded, only named.
+    if let Some(coordinates) = &settings.coordinates {
+        let label = coordinates.to_string();
+        let location = name_coordinates(
+            &client,
+            *coordinates,
+            &settings.reverse_geocoder,
+            &settings.language,
+        )
+        .await;
+        locations.push((label, location));
+    }
+
+    for ((city, query), candidates) in settings.city.iter().zip(&queries).zip(candidates) {
+        match candidates
+            .and_then(|candidates| selection::choose(&query.name, candidates, &selection))
+        {
+            Ok(location) => locations.push((city.clone(), location)),
+            Err(e) => {
+                print_error(city, &e);
+                errors.push(e);
+            }
+        }
+    }
+
+    let reports = join_all(locations.into_iter().map(|(city, location)| {
+        let (client, settings) = (&client, &settings);
+        async move { (city, fetch_report(client, location, settings).await) }
+    }))
+    .await;
+
+    let mut fetched = Vec::new();
+    for (city, report) in reports {
+        match report {
+            Ok(report) => fetched.push(report),
+            Err(e) => {
+                print_error(&city, &e);
+                errors.push(e);
+            }
+        }
+    }
+
+    if settings.output == OutputFormat::Compare {
+        let entries: Vec<(&Location, &CurrentWeather)> = fetched
+            .iter()
+            .map(|report| (&report.geo_info, &report.weather_info))
+            .collect();
+
+        if !entries.is_empty() {
+            WeatherOutput::print_comparison(&entries, &settings.display, &settings.language);
+        }
+
+        for report in &fetched {
+            errors.extend(print_forecasts(report, &settings));
+        }
+    } else {
+        for report in &fetched {
+            print_report(report, &settings);
+            errors.extend(print_forecasts(report, &settings));
+        }
+    }
+
+    match errors.first() {
+        Some(e) => ExitCode::from(e.exit_code()),
+        None => ExitCode::SUCCESS,
+    }
+}
+
+/// Names the place at `coordinates` in `language` with the configured reverse geocoding `backend`.
+async fn name_coordinates(
+    client: &Client,
+    coordinates: Coordinates,
+    backend: &Backend,
+    language: &Language,
+) -> Location {
+    match backend {
+        Backend::Nominatim => reverse::name(&Nominatim::new(client, language), coordinates).await,
+        Backend::None => Location::from_coordinates(coordinates),
+    }
+}
+
+/// Runs an action of the `locations` subcommand on the `saved` locations.
+/// Places are named in the language given with --lang or configured.
+async fn manage_locations(
+    args: &Args,
+    action: &LocationsAction,
+    saved: &mut SavedLocations,
+    client: &Client,
+) -> Result<(), CustomError> {
+    let language: Language = or_config(&args.lang, "language")?;
+
+    match action {
+        LocationsAction::Add {
+            alias,
+            city,
+            coords,
+            country,
+            pick,
+        } => {
+            let location = match coords {
+                Some(coordinates) => {
+                    let backend = config_enum("reverse_geocoder")?;
+                    name_coordinates(client, *coordinates, &backend, &language).await
                 }
-                _ => {
-                    eprintln!("Unexpected error type: {}", e);
+                None => {
+                    let query = LocationQuery::parse(
+                        city.as_deref().unwrap_or_default(),
+                        country.as_deref(),
+                    );
+                    let gazetteer = config_enum("gazetteer")?;
+                    let candidates =
+                        geolocation::get_info_for(client, &query, &language, &gazetteer).await?;
+                    selection::choose(&query.name, candidates, &Selection::new(*pick))?
                 }
+            };
+
+            let title = location.title();
+            match saved.add(alias, location)? {
+                Some(replaced) => println!(
+                    "Saved {} as '{}', replacing {}",
+                    title,
+                    alias,
+                    replaced.title()
+                ),
+                None => println!("Saved {} as '{}'", title, alias),
             }
-            return Ok(());
         }
-    };
+        LocationsAction::List => {
+            let width = saved.iter().map(|(alias, _)| alias.len()).max();
+            let Some(width) = width else {
+                println!(
+                    "No saved locations, add one with: weather-wand locations add <ALIAS> <CITY>"
+                );
+                return Ok(());
+            };
 
-    WeatherOutput::print_output(
-        &weather_info,
-        &geo_info,
+            for (alias, location) in saved.iter() {
+                println!(
+                    "{:<width$}  {} ({})",
+                    alias,
+                    location.title(),
+                    location.coordinates,
+                    width = width
+                );
+            }
+        }
+        LocationsAction::Remove { alias } => {
+            let location = saved.remove(alias)?;
+            println!("Removed '{}' ({})", alias, location.title());
+        }
+        LocationsAction::Rename { alias, new_alias } => {
+            saved.rename(alias, new_alias)?;
+            println!("Renamed '{}' to '{}'", alias, new_alias);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches the weather and the requested forecasts for the place `geo_info`.
+async fn fetch_report(
+    client: &Client,
+    mut geo_info: Location,
+    settings: &Settings,
+) -> Result<CityReport, CustomError> {
+    let temperature_unit = &settings.temperature_unit;
+    let windspeed_unit = &settings.windspeed_unit;
+
+    let weather_info = weather::get_info_for(
+        client,
+        &geo_info.coordinates,
         temperature_unit,
         windspeed_unit,
-        clock_display,
+    )
+    .await?;
+
+    // Places given as coordinates only learn their timezone from the weather API.
+    if geo_info.timezone.is_empty() {
+        geo_info.timezone = weather_info.timezone.clone();
+    }
+
+    let daily_forecast = match settings.days {
+        Some(days) => Some(
+            weather::get_daily_forecast_for(
+                client,
+                &geo_info.coordinates,
+                temperature_unit,
+                windspeed_unit,
+                days,
+            )
+            .await,
+        ),
+        None => None,
+    };
+
+    let hourly_forecast = match settings.hourly {
+        Some(hours) => Some(
+            weather::get_hourly_forecast_for(
+                client,
+                &geo_info.coordinates,
+                temperature_unit,
+                windspeed_unit,
+                hours,
+            )
+            .await,
+        ),
+        None => None,
+    };
+
+    Ok(CityReport {
+        geo_info,
+        weather_info,
+        daily_forecast,
+        hourly_forecast,
+    })
+}
+
+fn print_report(report: &CityReport, settings: &Settings) {
+    WeatherOutput::print_output(
+        &report.weather_info,
+        &report.geo_info,
+        &settings.display,
+        &settings.language,
+        &settings.fields,
     );
+}
 
-    Ok(())
+/// Prints the requested forecasts of a report and returns the errors of forecasts that failed.
+fn print_forecasts(report: &CityReport, settings: &Settings) -> Vec<CustomError> {
+    let clock_display = &settings.display;
+    let mut errors = Vec::new();
+
+    match &report.daily_forecast {
+        Some(Ok(forecast)) => WeatherOutput::print_daily_forecast(
+            forecast,
+            &report.geo_info,
+            clock_display,
+            &settings.language,
+        ),
+        Some(Err(e)) => {
+            eprintln!("Error: Could not fetch the daily forecast \n{}", e);
+            errors.push(e.clone());
+        }
+        None => {}
+    }
+
+    match &report.hourly_forecast {
+        Some(Ok(forecast)) => WeatherOutput::print_hourly_forecast(
+            forecast,
+            &report.geo_info,
+            clock_display,
+            &settings.language,
+        ),
+        Some(Err(e)) => {
+            eprintln!("Error: Could not fetch the hourly forecast \n{}", e);
+            errors.push(e.clone());
+        }
+        None => {}
+    }
+
+    errors
+}
+
+fn print_error(city: &str, e: &CustomError) {
+    match e {
+        CustomError::UnknownCity(_) => {
+            eprintln!(
+                "Error for \"{}\": {}\nPlease provide a valid city name",
+                city, e
+            );
+        }
+        CustomError::MissingField(_) | CustomError::Api(_) | CustomError::HttpStatus { .. } => {
+            eprintln!(
+                "Error for \"{}\": {}\nPlease provide valid arguments",
+                city, e
+            );
+        }
+        CustomError::Network { .. } | CustomError::Timeout(_) => {
+            eprintln!(
+                "Error for \"{}\": {}\nYou are not connected to the internet or the API is currently not available.",
+                city, e
+            );
+        }
+        CustomError::NotStored(_) => {
+            eprintln!(
+                "Error for \"{}\": {}\nRun the same command once while connected to the internet.",
+                city, e
+            );
+        }
+        CustomError::AmbiguousCity(_) => {
+            eprintln!(
+                "Error for \"{}\": {}\nPass --pick N to choose one of them.",
+                city, e
+            );
+        }
+        CustomError::MalformedJson(_) | CustomError::Config(_) | CustomError::SavedLocations(_) => {
+            eprintln!("Error for \"{}\": {}", city, e);
+        }
+    }
 }
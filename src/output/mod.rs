@@ -1,9 +1,15 @@
+pub mod translations;
+
 use super::geolocation::Location;
-use super::weather::CurrentWeather;
-use crate::config::args::{ClockDisplay, TemperatureUnit, WindspeedUnit};
-use chrono::{DateTime, Local, Utc};
+use super::weather::condition::{Intensity, WeatherCondition};
+use super::weather::{
+    CurrentWeather, DailyForecast, HourlyForecast, Temperature, WindDirection, WindSpeed, Zone,
+};
+use crate::config::args::{ClockDisplay, Field, Language, TemperatureUnit, WindspeedUnit};
+use chrono::{DateTime, Utc};
 use colored::Colorize;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::Duration;
+use translations::{display_width, format_date_time, name, pad, text, text_with};
 
 pub struct WeatherOutput {}
 
@@ -14,160 +20,884 @@ impl WeatherOutput {
     ///
     /// * `weather`: A reference to the `CurrentWeather` struct containing weather information.
     /// * `geo_info`: A reference to the `Location` struct containing geolocation information.
-    /// * `temperature_unit`: The unit for temperature display (e.g. Celsius or Fahrenheit).
-    /// * `windspeed_unit`: The unit for windspeed display (e.g. m/s or mph).
     /// * `clock_display`: The clock display format (12-hour or 24-hour).
+    /// * `language`: The language of the labels and dates.
+    /// * `fields`: The rows to show, in this order. Rows without a value are left out.
     pub fn print_output(
         weather: &CurrentWeather,
         geo_info: &Location,
-        temperature_unit: &TemperatureUnit,
-        windspeed_unit: &WindspeedUnit,
         clock_display: &ClockDisplay,
+        language: &Language,
+        fields: &[Field],
     ) {
         let header = format!(
-            "{} for {}, {}",
-            "  Current Weather Report",
-            geo_info.name.trim_matches('"'),
-            geo_info.country_code.trim_matches('"')
+            "{}  {}",
+            weather
+                .condition
+                .map_or("", |condition| condition_icon(condition, weather.is_day)),
+            text_with(language, "report_title", &geo_info.title())
         );
 
         let decoration_line = "─";
-        let decoration = decoration_line.repeat(header.len());
+        let decoration = decoration_line.repeat(display_width(&header) + 2);
 
-        let temperature_formatted = format!(
-            "{} {}",
-            weather.temperature,
-            match temperature_unit {
-                TemperatureUnit::Fahrenheit => "°F",
-                TemperatureUnit::Celsius => "°C",
-            }
-        );
+        let rows: Vec<(&str, &str, String)> = fields
+            .iter()
+            .filter_map(|field| report_row(*field, weather, geo_info, clock_display, language))
+            .collect();
 
-        let windspeed_formatted = format!(
-            "{} {}",
-            weather.windspeed,
-            match windspeed_unit {
-                WindspeedUnit::Ms => "m/s",
-                WindspeedUnit::Mph => "Mph",
-                WindspeedUnit::Kn => "Knots",
-                WindspeedUnit::Kmh => "Km/h",
-            }
-        );
+        let label_width = rows
+            .iter()
+            .map(|(_, key, _)| display_width(text(language, key)))
+            .max()
+            .unwrap_or_default()
+            + 2;
+
+        println!("┌{}┐", decoration);
+        println!("  {}", header.cyan().bold(),);
+        if let Some(condition) = weather.condition {
+            println!(
+                "     {}",
+                text(language, condition_key(condition)).cyan().bold()
+            );
+        }
+        if let Some(stale_age) = weather.stale_age {
+            println!(
+                "  {}",
+                format!(
+                    "  {}",
+                    text_with(language, "offline", &format_age(stale_age))
+                )
+                .yellow()
+                .bold()
+            );
+        }
+        for (icon, key, value) in rows {
+            println!(
+                "  {}  {}{}",
+                icon,
+                pad(&format!("{}:", text(language, key)), label_width),
+                value.bright_blue()
+            );
+        }
+        println!("└{}┘", decoration);
+    }
+
+    /// Prints the current weather of several cities side by side, one column per city.
+    /// The warmest, coldest and windiest cities are highlighted.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries`: The `Location` and `CurrentWeather` of every city to compare.
+    /// * `clock_display`: The clock display format (12-hour or 24-hour).
+    /// * `language`: The language of the labels and dates.
+    pub fn print_comparison(
+        entries: &[(&Location, &CurrentWeather)],
+        clock_display: &ClockDisplay,
+        language: &Language,
+    ) {
+        // Compared in one unit, in case the cities' weather was requested in different ones.
+        let temperatures: Vec<Option<f64>> = entries
+            .iter()
+            .map(|(_, weather)| Some(weather.temperature.to(TemperatureUnit::Celsius).value))
+            .collect();
+        let windspeeds: Vec<Option<f64>> = entries
+            .iter()
+            .map(|(_, weather)| Some(weather.windspeed.to(WindspeedUnit::Ms).value))
+            .collect();
 
-        let day_night_icon = if weather.is_day == "1" {
-            " "
-        } else {
-            " "
-        };
+        let (coldest, warmest) = extremes(&temperatures);
+        let (_, windiest) = extremes(&windspeeds);
 
-        let formatted_date_local = format_date(weather.timestamp, clock_display).local;
+        let city_titles: Vec<String> = entries
+            .iter()
+            .map(|(geo_info, _)| geo_info.title())
+            .collect();
+
+        let rows: Vec<(String, Vec<(String, Highlight)>)> = vec![
+            (
+                format!("󱣖  {}", text(language, "temperature")),
+                entries
+                    .iter()
+                    .zip(&temperatures)
+                    .map(|((_, weather), temperature)| {
+                        let highlight = match temperature {
+                            t if t.is_some() && *t == warmest => Highlight::Warmest,
+                            t if t.is_some() && *t == coldest => Highlight::Coldest,
+                            _ => Highlight::None,
+                        };
+                        (format_temperature(&weather.temperature), highlight)
+                    })
+                    .collect(),
+            ),
+            (
+                format!("  {}", text(language, "wind_speed")),
+                entries
+                    .iter()
+                    .zip(&windspeeds)
+                    .map(|((_, weather), windspeed)| {
+                        let highlight = match windspeed {
+                            w if w.is_some() && *w == windiest => Highlight::Windiest,
+                            _ => Highlight::None,
+                        };
+                        (format_windspeed(&weather.windspeed), highlight)
+                    })
+                    .collect(),
+            ),
+            (
+                format!("  {}", text(language, "day_night")),
+                entries
+                    .iter()
+                    .map(|(_, weather)| {
+                        let day_night = if weather.is_day {
+                            format!(" {}", text(language, "day"))
+                        } else {
+                            format!(" {}", text(language, "night"))
+                        };
+                        (day_night, Highlight::None)
+                    })
+                    .collect(),
+            ),
+            (
+                format!("  {}", text(language, "update_time")),
+                entries
+                    .iter()
+                    .map(|(_, weather)| {
+                        let update_time = format_in_zone(
+                            &weather.timestamp,
+                            &weather.zone,
+                            time_format(clock_display, language),
+                            language,
+                        );
+                        match weather.stale_age {
+                            Some(stale_age) => (
+                                format!(
+                                    "{} ({})",
+                                    update_time,
+                                    text_with(language, "stale", &format_age(stale_age))
+                                ),
+                                Highlight::Stale,
+                            ),
+                            None => (update_time, Highlight::None),
+                        }
+                    })
+                    .collect(),
+            ),
+            (
+                format!("  {}", text(language, "timezone")),
+                entries
+                    .iter()
+                    .map(|(geo_info, _)| {
+                        (
+                            geo_info.timezone.trim_matches('"').replace('_', " "),
+                            Highlight::None,
+                        )
+                    })
+                    .collect(),
+            ),
+        ];
+
+        let label_width = rows
+            .iter()
+            .map(|(label, _)| display_width(label))
+            .max()
+            .unwrap_or_default()
+            + 2;
+        let column_widths: Vec<usize> = city_titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| {
+                rows.iter()
+                    .map(|(_, cells)| display_width(&cells[i].0))
+                    .chain([display_width(title)])
+                    .max()
+                    .unwrap_or_default()
+                    + 2
+            })
+            .collect();
+
+        let header = format!("  {}", text(language, "comparison_title"));
+        let width = label_width + column_widths.iter().sum::<usize>() + 2;
+        let decoration = "─".repeat(width.max(display_width(&header) + 2));
 
         println!("┌{}┐", decoration);
-        println!("  {}", header.cyan().bold(),);
-        println!("  󱣖  Temperature: {}", temperature_formatted.bright_blue());
-        println!("    Wind Speed:  {}", windspeed_formatted.bright_blue());
-        println!(
-            "  󰅆  City:        {}",
-            geo_info.name.trim_matches('"').bright_blue()
-        );
+        println!("  {}", header.cyan().bold());
 
-        let area = format!("{}, {}", geo_info.region, geo_info.country).bright_blue();
-        println!("    Area:        {}", area);
+        let titles: String = city_titles
+            .iter()
+            .zip(&column_widths)
+            .map(|(title, width)| pad(title, *width))
+            .collect();
+        println!("  {}{}", pad("", label_width), titles.bold());
 
-        let coordinates = format!(
-            "{}, {}",
-            geo_info.coordinates.latitude, geo_info.coordinates.longitude
-        )
-        .bright_blue();
+        for (label, cells) in &rows {
+            let line: String = cells
+                .iter()
+                .zip(&column_widths)
+                .map(|((cell, highlight), width)| {
+                    let padded = pad(cell, *width);
+                    match highlight {
+                        Highlight::Warmest => padded.bright_red().bold().to_string(),
+                        Highlight::Coldest => padded.bright_cyan().bold().to_string(),
+                        Highlight::Windiest => padded.bright_yellow().bold().to_string(),
+                        Highlight::Stale => padded.yellow().to_string(),
+                        Highlight::None => padded.bright_blue().to_string(),
+                    }
+                })
+                .collect();
+            println!("  {}{}", pad(label, label_width), line);
+        }
 
-        println!(
-            "    Timezone:    {}",
-            geo_info
-                .timezone
-                .trim_matches('"')
-                .replace('_', " ")
-                .bright_blue()
+        if entries.len() > 1 {
+            println!(
+                "  {} {} {}",
+                text(language, "warmest").bright_red().bold(),
+                text(language, "coldest").bright_cyan().bold(),
+                text(language, "windiest").bright_yellow().bold()
+            );
+        }
+
+        println!("└{}┘", decoration);
+    }
+
+    /// Prints the daily forecast to the console as a table with one row per day.
+    ///
+    /// # Arguments
+    ///
+    /// * `forecast`: A reference to the `DailyForecast` struct containing the forecast days.
+    /// * `geo_info`: A reference to the `Location` struct containing geolocation information.
+    /// * `clock_display`: The clock display format (12-hour or 24-hour).
+    /// * `language`: The language of the labels and dates.
+    pub fn print_daily_forecast(
+        forecast: &DailyForecast,
+        geo_info: &Location,
+        clock_display: &ClockDisplay,
+        language: &Language,
+    ) {
+        let header = format!(
+            "  {}",
+            text_with(language, "daily_title", &geo_info.title())
         );
 
-        println!("    Coordinates: {}", coordinates);
+        let cells: Vec<Vec<String>> = forecast
+            .days
+            .iter()
+            .map(|day| {
+                vec![
+                    format_in_zone(
+                        &day.timestamp,
+                        &forecast.zone,
+                        text(language, "day_format"),
+                        language,
+                    ),
+                    or_missing(day.temperature_min.as_ref().map(format_temperature)),
+                    or_missing(day.temperature_max.as_ref().map(format_temperature)),
+                    or_missing(day.precipitation_sum.map(|sum| format!("{:.1} mm", sum))),
+                    or_missing(day.windspeed_max.as_ref().map(format_windspeed)),
+                    or_missing(day.condition.map(|condition| {
+                        format!(
+                            "{} {}",
+                            condition_icon(condition, true),
+                            text(language, condition_key(condition))
+                        )
+                    })),
+                    or_missing(day.sunrise.as_ref().map(|sunrise| {
+                        format_in_zone(
+                            sunrise,
+                            &forecast.zone,
+                            time_format(clock_display, language),
+                            language,
+                        )
+                    })),
+                    or_missing(day.sunset.as_ref().map(|sunset| {
+                        format_in_zone(
+                            sunset,
+                            &forecast.zone,
+                            time_format(clock_display, language),
+                            language,
+                        )
+                    })),
+                ]
+            })
+            .collect();
+
+        let columns = [
+            ("date", 12),
+            ("min", 10),
+            ("max", 10),
+            ("precipitation", 10),
+            ("wind", 13),
+            ("weather", 5),
+            ("sunrise", 10),
+            ("sunset", 0),
+        ];
+
+        print_table(&header, forecast.stale_age, &columns, &cells, language);
+    }
 
-        println!(
-            "    Update Time: {} {} {}",
-            formatted_date_local.bright_blue(),
-            "|".bright_blue(),
-            day_night_icon.bright_blue()
+    /// Prints the hourly forecast to the console as a timeline with one row per hour.
+    ///
+    /// # Arguments
+    ///
+    /// * `forecast`: A reference to the `HourlyForecast` struct containing the forecast hours.
+    /// * `geo_info`: A reference to the `Location` struct containing geolocation information.
+    /// * `clock_display`: The clock display format (12-hour or 24-hour).
+    /// * `language`: The language of the labels and dates.
+    pub fn print_hourly_forecast(
+        forecast: &HourlyForecast,
+        geo_info: &Location,
+        clock_display: &ClockDisplay,
+        language: &Language,
+    ) {
+        let header = format!(
+            "  {}",
+            text_with(language, "hourly_title", &geo_info.title())
         );
 
-        println!("└{}┘", decoration);
+        let cells: Vec<Vec<String>> =
+            forecast
+                .hours
+                .iter()
+                .map(|hour| {
+                    vec![
+                        format_date(&hour.timestamp, &forecast.zone, clock_display, language),
+                        or_missing(hour.temperature.as_ref().map(format_temperature)),
+                        or_missing(
+                            hour.precipitation_probability
+                                .map(|probability| format!("{} %", probability)),
+                        ),
+                        or_missing(hour.windspeed.as_ref().map(
+                            |windspeed| match hour.winddirection {
+                                Some(direction) => format!(
+                                    "{} {}°",
+                                    format_windspeed(windspeed),
+                                    direction.degrees
+                                ),
+                                None => format_windspeed(windspeed),
+                            },
+                        )),
+                        or_missing(
+                            hour.cloudcover
+                                .map(|cloudcover| format!("{} %", cloudcover)),
+                        ),
+                    ]
+                })
+                .collect();
+
+        let columns = [
+            ("time", 22),
+            ("temperature_short", 10),
+            ("precipitation", 8),
+            ("wind", 18),
+            ("clouds", 0),
+        ];
+
+        print_table(&header, forecast.stale_age, &columns, &cells, language);
+    }
+}
+
+/// Marks a cell of the comparison table that holds an extreme value.
+enum Highlight {
+    None,
+    Warmest,
+    Coldest,
+    Windiest,
+    Stale,
+}
+
+/// Returns the icon, the label key and the value of the report row `field`,
+/// or `None` if there is no value to show, e.g. for a place without elevation.
+fn report_row(
+    field: Field,
+    weather: &CurrentWeather,
+    geo_info: &Location,
+    clock_display: &ClockDisplay,
+    language: &Language,
+) -> Option<(&'static str, &'static str, String)> {
+    let row = match field {
+        Field::Temperature => ("󱣖", "temperature", format_temperature(&weather.temperature)),
+        Field::FeelsLike => (
+            "",
+            "feels_like",
+            format_temperature(weather.apparent_temperature.as_ref()?),
+        ),
+        Field::Humidity => ("", "humidity", format!("{}%", weather.humidity?)),
+        Field::Pressure => (
+            "",
+            "pressure",
+            format!("{:.1} hPa", weather.surface_pressure?),
+        ),
+        Field::Precipitation => (
+            "",
+            "precipitation_now",
+            format!("{:.1} mm", weather.precipitation?),
+        ),
+        Field::CloudCover => ("", "cloud_cover", format!("{}%", weather.cloudcover?)),
+        Field::Visibility => ("󰈈", "visibility", format_visibility(weather.visibility?)),
+        Field::WindSpeed => {
+            let beaufort = weather.windspeed.beaufort();
+            (
+                "",
+                "wind_speed",
+                format!(
+                    "{} (Bft {}, {})",
+                    format_windspeed(&weather.windspeed),
+                    beaufort,
+                    name(language, "beaufort", beaufort as usize)
+                ),
+            )
+        }
+        Field::WindDirection => (
+            "󰆌",
+            "wind_direction",
+            format_winddirection(weather.winddirection.as_ref()?, language),
+        ),
+        Field::Gusts => ("", "gusts", format_windspeed(weather.windgusts.as_ref()?)),
+        Field::UvIndex => ("", "uv_index", format!("{:.1}", weather.uv_index?)),
+        Field::City => ("󰅆", "city", geo_info.name.trim_matches('"').to_string()),
+        // Places named after their coordinates or remote places may lack a region and country.
+        Field::Area => ("", "area", geo_info.area()?),
+        Field::Timezone => (
+            "",
+            "timezone",
+            geo_info.timezone.trim_matches('"').replace('_', " "),
+        ),
+        Field::Coordinates => ("", "coordinates", geo_info.coordinates.to_string()),
+        Field::Elevation => ("󰖃", "elevation", format!("{} m", geo_info.elevation?)),
+        Field::UpdateTime => {
+            let day_night_icon = if weather.is_day { " " } else { " " };
+            (
+                "",
+                "update_time",
+                format!(
+                    "{} | {}",
+                    format_date(&weather.timestamp, &weather.zone, clock_display, language),
+                    day_night_icon
+                ),
+            )
+        }
+    };
+
+    Some(row)
+}
+
+/// Formats a visibility given in meters, in kilometers from 1 km on, e.g. "24.1 km".
+fn format_visibility(meters: f64) -> String {
+    if meters >= 1000.0 {
+        format!("{:.1} km", meters / 1000.0)
+    } else {
+        format!("{:.0} m", meters)
+    }
+}
+
+/// Returns the smallest and largest of the given values, or `None` for both
+/// if there are fewer than two distinct values to compare.
+fn extremes(values: &[Option<f64>]) -> (Option<f64>, Option<f64>) {
+    let known = values.iter().flatten().copied();
+    let min = known.clone().reduce(f64::min);
+    let max = known.reduce(f64::max);
+
+    if min == max {
+        (None, None)
+    } else {
+        (min, max)
+    }
+}
+
+/// Prints a boxed table with a `header`, a line of column titles and a row for each of the `cells`.
+/// A `stale_age` is noted below the header like in the report, for stored forecasts.
+///
+/// `columns` holds the catalog key of each column's title and the width it takes at least.
+/// Columns are widened to fit their title and cells in `language`, keeping two spaces between them.
+fn print_table(
+    header: &str,
+    stale_age: Option<Duration>,
+    columns: &[(&'static str, usize)],
+    cells: &[Vec<String>],
+    language: &Language,
+) {
+    let titles: Vec<String> = columns
+        .iter()
+        .map(|(key, _)| text(language, key).to_string())
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (_, min_width))| {
+            cells
+                .iter()
+                .chain([&titles])
+                .map(|row| display_width(&row[i]) + 2)
+                .chain([*min_width])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let line = |row: &[String]| -> String {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, width)| pad(cell, *width))
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    };
+
+    let column_titles = line(&titles);
+    let rows: Vec<String> = cells.iter().map(|row| line(row)).collect();
+    let note = stale_age.map(|age| text_with(language, "offline", &format_age(age)));
+
+    let width = rows
+        .iter()
+        .map(|row| display_width(row))
+        .chain([display_width(header), display_width(&column_titles)])
+        .chain(note.iter().map(|note| display_width(note)))
+        .max()
+        .unwrap_or_default()
+        + 2;
+    let decoration = "─".repeat(width);
+
+    println!("┌{}┐", decoration);
+    println!("  {}", header.cyan().bold());
+    if let Some(note) = note {
+        println!("  {}", note.yellow().bold());
+    }
+    println!("  {}", column_titles.bold());
+    for row in rows {
+        println!("  {}", row.bright_blue());
+    }
+    println!("└{}┘", decoration);
+}
+
+fn temperature_symbol(temperature_unit: &TemperatureUnit) -> &'static str {
+    match temperature_unit {
+        TemperatureUnit::Fahrenheit => "°F",
+        TemperatureUnit::Celsius => "°C",
+    }
+}
+
+fn windspeed_symbol(windspeed_unit: &WindspeedUnit) -> &'static str {
+    match windspeed_unit {
+        WindspeedUnit::Ms => "m/s",
+        WindspeedUnit::Mph => "Mph",
+        WindspeedUnit::Kn => "Knots",
+        WindspeedUnit::Kmh => "Km/h",
     }
 }
 
-struct FormattedDates {
-    #[allow(dead_code)]
-    utc: String,
-    local: String,
+/// Formats a temperature with one decimal and its unit, e.g. "8.8 °C".
+fn format_temperature(temperature: &Temperature) -> String {
+    format!(
+        "{:.1} {}",
+        temperature.value,
+        temperature_symbol(&temperature.unit)
+    )
 }
 
-fn format_date(timestamp: u64, clock_display: &ClockDisplay) -> FormattedDates {
-    let converted_date: SystemTime = UNIX_EPOCH + std::time::Duration::from_secs(timestamp);
+/// Formats a wind speed with one decimal and its unit, e.g. "12.7 Km/h".
+fn format_windspeed(windspeed: &WindSpeed) -> String {
+    format!(
+        "{:.1} {}",
+        windspeed.value,
+        windspeed_symbol(&windspeed.unit)
+    )
+}
+
+/// Returns the Nerd Font icon of a weather condition, in its night variant unless `is_day`.
+fn condition_icon(condition: WeatherCondition, is_day: bool) -> &'static str {
+    use WeatherCondition::*;
 
-    let format = match clock_display {
-        ClockDisplay::_12h => "%b %-e, %Y %I:%M %p",
-        ClockDisplay::_24h => "%b %-e, %Y %H:%M",
+    let (day, night) = match condition {
+        Clear => ("", ""),
+        MainlyClear => ("", ""),
+        PartlyCloudy => ("", ""),
+        Overcast => ("", ""),
+        Fog | RimeFog => ("", ""),
+        Drizzle(_) => ("", ""),
+        FreezingDrizzle(_) | FreezingRain(_) => ("", ""),
+        Rain(_) => ("", ""),
+        RainShowers(_) => ("", ""),
+        Snow(_) | SnowGrains => ("", ""),
+        SnowShowers(_) => ("", ""),
+        Thunderstorm => ("", ""),
+        ThunderstormWithHail(_) => ("", ""),
     };
 
-    let formatted_date_utc = format!("{}", DateTime::<Utc>::from(converted_date).format(format));
-    let formatted_date_local =
-        format!("{}", DateTime::<Local>::from(converted_date).format(format));
+    if is_day {
+        day
+    } else {
+        night
+    }
+}
+
+/// Returns the catalog key of the description of a weather condition, e.g. `rain_heavy`.
+fn condition_key(condition: WeatherCondition) -> &'static str {
+    use Intensity::{Heavy, Light, Moderate};
+    use WeatherCondition::*;
 
-    FormattedDates {
-        utc: formatted_date_utc,
-        local: formatted_date_local,
+    match condition {
+        Clear => "clear",
+        MainlyClear => "mainly_clear",
+        PartlyCloudy => "partly_cloudy",
+        Overcast => "overcast",
+        Fog => "fog",
+        RimeFog => "rime_fog",
+        Drizzle(Light) => "drizzle_light",
+        Drizzle(Moderate) => "drizzle_moderate",
+        Drizzle(Heavy) => "drizzle_heavy",
+        FreezingDrizzle(Light) => "freezing_drizzle_light",
+        FreezingDrizzle(_) => "freezing_drizzle_heavy",
+        Rain(Light) => "rain_light",
+        Rain(Moderate) => "rain_moderate",
+        Rain(Heavy) => "rain_heavy",
+        FreezingRain(Light) => "freezing_rain_light",
+        FreezingRain(_) => "freezing_rain_heavy",
+        Snow(Light) => "snow_light",
+        Snow(Moderate) => "snow_moderate",
+        Snow(Heavy) => "snow_heavy",
+        SnowGrains => "snow_grains",
+        RainShowers(Light) => "rain_showers_light",
+        RainShowers(Moderate) => "rain_showers_moderate",
+        RainShowers(Heavy) => "rain_showers_heavy",
+        SnowShowers(Light) => "snow_showers_light",
+        SnowShowers(_) => "snow_showers_heavy",
+        Thunderstorm => "thunderstorm",
+        ThunderstormWithHail(Light) => "thunderstorm_hail_light",
+        ThunderstormWithHail(_) => "thunderstorm_hail_heavy",
     }
 }
 
+/// Formats a wind direction in degrees, as compass point and as an arrow pointing where
+/// the wind blows to, e.g. "230° SW ↗".
+fn format_winddirection(direction: &WindDirection, language: &Language) -> String {
+    const ARROWS: [&str; 8] = ["↓", "↙", "←", "↖", "↑", "↗", "→", "↘"];
+
+    format!(
+        "{}° {} {}",
+        direction.degrees,
+        name(language, "compass_points", direction.compass_point()),
+        ARROWS[(f64::from(direction.degrees % 360) / 45.0).round() as usize % 8]
+    )
+}
+
+/// Returns the formatted value, or a dash if the API has no data for it.
+fn or_missing(formatted: Option<String>) -> String {
+    formatted.unwrap_or_else(|| "-".to_string())
+}
+
+fn time_format(clock_display: &ClockDisplay, language: &Language) -> &'static str {
+    match clock_display {
+        ClockDisplay::_12h => text(language, "time_format_12h"),
+        ClockDisplay::_24h => text(language, "time_format_24h"),
+    }
+}
+
+/// Formats a point in time in the `zone` of the location with the offset to UTC in effect then,
+/// so that forecast dates and sun times are shown as they are at the location.
+fn format_in_zone(
+    date_time: &DateTime<Utc>,
+    zone: &Zone,
+    format: &str,
+    language: &Language,
+) -> String {
+    format_date_time(
+        &date_time.with_timezone(&zone.offset_at(date_time)),
+        format,
+        language,
+    )
+}
+
+/// Formats the age of stored data in the largest fitting units, e.g. "2 h 5 min" or "3 d 4 h".
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+
+    match (days, hours) {
+        (0, 0) => format!("{} min", minutes),
+        (0, _) => format!("{} h {} min", hours, minutes),
+        _ => format!("{} d {} h", days, hours),
+    }
+}
+
+/// Formats the date and time of `date_time` as it is at the location in `zone`.
+fn format_date(
+    date_time: &DateTime<Utc>,
+    zone: &Zone,
+    clock_display: &ClockDisplay,
+    language: &Language,
+) -> String {
+    let format = format!(
+        "{} {}",
+        text(language, "date_format"),
+        time_format(clock_display, language)
+    );
+
+    format_in_zone(date_time, zone, &format, language)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::format_date;
-    use crate::config::args::ClockDisplay;
-    use chrono::{Local, TimeZone, Utc};
+    use super::{
+        condition_key, extremes, format_age, format_date, format_in_zone, format_winddirection,
+    };
+    use crate::config::args::{ClockDisplay, Language};
+    use crate::output::translations::text;
+    use crate::weather::condition::WeatherCondition;
+    use crate::weather::{WindDirection, Zone};
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use chrono_tz::Tz;
+    use std::time::Duration;
 
     #[test]
     fn format_date_returns_correctly_formatted_dates() {
-        let timestamp = 1672531200;
+        let timestamp = &Utc.timestamp_opt(1672531200, 0).unwrap();
+        let utc = &Zone::Fixed(FixedOffset::east_opt(0).unwrap());
         assert_eq!(
             "Jan 1, 2023 12:00 AM",
-            format_date(timestamp, &ClockDisplay::_12h).utc
+            format_date(timestamp, utc, &ClockDisplay::_12h, &Language::En)
         );
         assert_eq!(
             "Jan 1, 2023 00:00",
-            format_date(timestamp, &ClockDisplay::_24h).utc
+            format_date(timestamp, utc, &ClockDisplay::_24h, &Language::En)
+        );
+
+        // New York (UTC-5) is still in the old year.
+        let new_york = &Zone::Fixed(FixedOffset::west_opt(5 * 3600).unwrap());
+        assert_eq!(
+            "Dec 31, 2022 07:00 PM",
+            format_date(timestamp, new_york, &ClockDisplay::_12h, &Language::En)
+        );
+        assert_eq!(
+            "Dec 31, 2022 19:00",
+            format_date(timestamp, new_york, &ClockDisplay::_24h, &Language::En)
+        );
+    }
+
+    #[test]
+    fn format_date_uses_the_date_format_of_the_language() {
+        let timestamp = &Utc.timestamp_opt(1672531200, 0).unwrap();
+        let utc = &Zone::Fixed(FixedOffset::east_opt(0).unwrap());
+
+        assert_eq!(
+            "1. Jan 2023 00:00",
+            format_date(timestamp, utc, &ClockDisplay::_24h, &Language::De)
+        );
+        assert_eq!(
+            "2023年1月1日 午前12:00",
+            format_date(timestamp, utc, &ClockDisplay::_12h, &Language::Ja)
+        );
+    }
+
+    #[test]
+    fn format_in_zone_uses_the_locations_offset() {
+        // 2023-12-16 23:30 UTC is already the next day in Berlin (UTC+1).
+        let timestamp = &Utc.timestamp_opt(1702769400, 0).unwrap();
+        let (utc, berlin) = (
+            &Zone::Fixed(FixedOffset::east_opt(0).unwrap()),
+            &Zone::Named(Tz::Europe__Berlin),
+        );
+
+        assert_eq!(
+            "Sat, Dec 16 23:30",
+            format_in_zone(timestamp, utc, "%a, %b %-e %H:%M", &Language::En)
+        );
+        assert_eq!(
+            "Sun, Dec 17 00:30",
+            format_in_zone(timestamp, berlin, "%a, %b %-e %H:%M", &Language::En)
         );
+        assert_eq!(
+            "So, 17. Dez 00:30",
+            format_in_zone(timestamp, berlin, "%a, %-e. %b %H:%M", &Language::De)
+        );
+    }
+
+    #[test]
+    fn format_in_zone_follows_daylight_saving_time_changes() {
+        // Berlin switched from CEST (UTC+2) to CET (UTC+1) at 2023-10-29 01:00 UTC,
+        // while the API only sends the offset at the start of the forecast.
+        let berlin = &Zone::Named(Tz::Europe__Berlin);
+        let before = &Utc.with_ymd_and_hms(2023, 10, 28, 22, 0, 0).unwrap();
+        let after = &Utc.with_ymd_and_hms(2023, 10, 29, 10, 0, 0).unwrap();
+
+        assert_eq!(
+            "00:00",
+            format_in_zone(before, berlin, "%H:%M", &Language::En)
+        );
+        assert_eq!(
+            "11:00",
+            format_in_zone(after, berlin, "%H:%M", &Language::En)
+        );
+
+        let fixed = &Zone::Fixed(FixedOffset::east_opt(7200).unwrap());
+        assert_eq!(
+            "12:00",
+            format_in_zone(after, fixed, "%H:%M", &Language::En)
+        );
+    }
+
+    #[test]
+    fn format_winddirection_shows_compass_point_and_arrow() {
+        let direction = |degrees| WindDirection { degrees };
+
+        assert_eq!(
+            "230° SW ↗",
+            format_winddirection(&direction(230), &Language::En)
+        );
+        assert_eq!(
+            "320° NW ↘",
+            format_winddirection(&direction(320), &Language::En)
+        );
+        assert_eq!(
+            "90° O ←",
+            format_winddirection(&direction(90), &Language::De)
+        );
+        assert_eq!(
+            "0° 北 ↓",
+            format_winddirection(&direction(0), &Language::Ja)
+        );
+    }
+
+    #[test]
+    fn every_weather_condition_is_described() {
+        for condition in (0..=u8::MAX).filter_map(WeatherCondition::from_code) {
+            let key = condition_key(condition);
+            assert_ne!(text(&Language::En, key), key, "{:?}", condition);
+        }
+
+        assert_eq!(
+            text(
+                &Language::De,
+                condition_key(WeatherCondition::from_code(65).unwrap())
+            ),
+            "Starker Regen"
+        );
+    }
 
-        // Convert UTC to local
-        let local_time_12h_expected = Utc
-            .timestamp_opt(timestamp as i64, 0)
-            .earliest()
-            .unwrap()
-            .with_timezone(&Local)
-            .format("%b %-e, %Y %I:%M %p")
-            .to_string();
+    #[test]
+    fn extremes_returns_min_and_max_of_known_values() {
         assert_eq!(
-            local_time_12h_expected,
-            format_date(timestamp, &ClockDisplay::_12h).local
+            (Some(-3.5), Some(21.0)),
+            extremes(&[Some(4.0), None, Some(-3.5), Some(21.0)])
         );
+    }
+
+    #[test]
+    fn extremes_returns_none_without_distinct_values() {
+        assert_eq!((None, None), extremes(&[Some(4.0), Some(4.0)]));
+        assert_eq!((None, None), extremes(&[Some(4.0)]));
+        assert_eq!((None, None), extremes(&[]));
+    }
 
-        // Convert UTC to local
-        let local_time_24h_expected = Utc
-            .timestamp_opt(timestamp as i64, 0)
-            .earliest()
-            .unwrap()
-            .with_timezone(&Local)
-            .format("%b %-e, %Y %H:%M")
-            .to_string();
+    #[test]
+    fn format_age_uses_largest_fitting_units() {
+        assert_eq!("0 min", format_age(Duration::from_secs(30)));
+        assert_eq!("42 min", format_age(Duration::from_secs(42 * 60)));
+        assert_eq!(
+            "2 h 5 min",
+            format_age(Duration::from_secs(2 * 3600 + 5 * 60))
+        );
         assert_eq!(
-            local_time_24h_expected,
-            format_date(timestamp, &ClockDisplay::_24h).local
+            "3 d 4 h",
+            format_age(Duration::from_secs(3 * 86400 + 4 * 3600))
         );
     }
 }
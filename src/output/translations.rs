@@ -0,0 +1,162 @@
+use crate::config::args::Language;
+use chrono::{DateTime, Locale, TimeZone};
+use serde_json::Value;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// The output labels and date formats of every language, see files/translations.json.
+static CATALOG: OnceLock<Value> = OnceLock::new();
+
+fn catalog() -> &'static Value {
+    CATALOG.get_or_init(|| {
+        serde_json::from_str(include_str!("files/translations.json"))
+            .expect("the bundled translations are valid JSON")
+    })
+}
+
+/// Looks up the entry `key` for `language`, falling back to English if it is not translated.
+fn entry(language: &Language, key: &str) -> Option<&'static Value> {
+    let catalog = catalog();
+
+    catalog
+        .get(language.to_string())
+        .and_then(|strings| strings.get(key))
+        .or_else(|| catalog.get(Language::En.to_string())?.get(key))
+}
+
+/// Returns the label `key` in `language`, e.g. "Temperatur" for `temperature` in German.
+/// Unknown keys are returned as they are.
+pub fn text(language: &Language, key: &'static str) -> &'static str {
+    entry(language, key).and_then(Value::as_str).unwrap_or(key)
+}
+
+/// Returns the label `key` in `language` with its `{}` replaced by `value`,
+/// e.g. "Tagesvorhersage für Berlin, DE".
+pub fn text_with(language: &Language, key: &'static str, value: &str) -> String {
+    text(language, key).replacen("{}", value, 1)
+}
+
+/// Returns the `index`-th name of the list `key` in `language`, e.g. the month names.
+pub fn name(language: &Language, key: &str, index: usize) -> &'static str {
+    entry(language, key)
+        .and_then(|names| names.get(index))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// Formats `date_time` like chrono's `format`, but with the month and weekday names
+/// (`%b`, `%a`) and AM/PM (`%p`) of the locale of `language`.
+pub fn format_date_time<Tz>(date_time: &DateTime<Tz>, format: &str, language: &Language) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    date_time
+        .format_localized(format, locale(language))
+        .to_string()
+}
+
+/// Returns the locale whose month and weekday names are used for `language`.
+fn locale(language: &Language) -> Locale {
+    match language {
+        Language::En => Locale::en_US,
+        Language::De => Locale::de_DE,
+        Language::Ja => Locale::ja_JP,
+    }
+}
+
+/// Returns how many terminal columns `text` takes up, counting wide characters
+/// like those of Japanese as two columns.
+pub fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|char| match char as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Pads `text` with spaces to `width` terminal columns, like `format!("{:<width$}")`
+/// but aware of wide characters.
+pub fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        text,
+        " ".repeat(width.saturating_sub(display_width(text)))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{catalog, display_width, format_date_time, pad, text, text_with};
+    use crate::config::args::Language;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn every_language_translates_every_english_entry() {
+        let english = catalog()["en"].as_object().unwrap();
+
+        for language in ["de", "ja"] {
+            let translated = catalog()[language].as_object().unwrap();
+            for (key, value) in english {
+                let translation = &translated[key];
+                assert!(!translation.is_null(), "{} is missing {}", language, key);
+                if let Some(names) = value.as_array() {
+                    assert_eq!(
+                        names.len(),
+                        translation.as_array().unwrap().len(),
+                        "{}",
+                        key
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn text_translates_labels_and_titles() {
+        assert_eq!(text(&Language::En, "temperature"), "Temperature");
+        assert_eq!(text(&Language::De, "temperature"), "Temperatur");
+        assert_eq!(text(&Language::Ja, "temperature"), "気温");
+        assert_eq!(
+            text_with(&Language::Ja, "daily_title", "東京, JP"),
+            "東京, JPの日別予報"
+        );
+    }
+
+    #[test]
+    fn format_date_time_uses_localized_names() {
+        let date_time = Utc.with_ymd_and_hms(2023, 3, 5, 14, 30, 0).unwrap();
+
+        assert_eq!(
+            format_date_time(&date_time, "%a, %b %-e %I:%M %p", &Language::En),
+            "Sun, Mar 5 02:30 PM"
+        );
+        assert_eq!(
+            format_date_time(&date_time, "%a, %-e. %b", &Language::De),
+            "So, 5. Mär"
+        );
+        assert_eq!(
+            format_date_time(&date_time, "%-m月%-e日(%a) %p%I:%M", &Language::Ja),
+            "3月5日(日) 午後02:30"
+        );
+    }
+
+    #[test]
+    fn pad_counts_wide_characters_twice() {
+        assert_eq!(display_width("気温"), 4);
+        assert_eq!(pad("気温", 6), "気温  ");
+        assert_eq!(pad("Temp.", 7), "Temp.  ");
+        assert_eq!(pad("Temperature", 4), "Temperature");
+    }
+}
@@ -0,0 +1,99 @@
+/// The intensity of precipitation. Some conditions only know light and heavy,
+/// e.g. freezing rain, so they are never `Moderate`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Intensity {
+    Light,
+    Moderate,
+    Heavy,
+}
+
+/// The weather condition described by a WMO weather interpretation code,
+/// see the `weathercode` of <https://open-meteo.com/en/docs>.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WeatherCondition {
+    Clear,
+    MainlyClear,
+    PartlyCloudy,
+    Overcast,
+    Fog,
+    RimeFog,
+    Drizzle(Intensity),
+    FreezingDrizzle(Intensity),
+    Rain(Intensity),
+    FreezingRain(Intensity),
+    Snow(Intensity),
+    SnowGrains,
+    RainShowers(Intensity),
+    SnowShowers(Intensity),
+    Thunderstorm,
+    ThunderstormWithHail(Intensity),
+}
+
+impl WeatherCondition {
+    /// Interprets a WMO weather code, returning `None` for codes the API does not use.
+    pub fn from_code(code: u8) -> Option<Self> {
+        use Intensity::{Heavy, Light, Moderate};
+        use WeatherCondition::*;
+
+        let condition = match code {
+            0 => Clear,
+            1 => MainlyClear,
+            2 => PartlyCloudy,
+            3 => Overcast,
+            45 => Fog,
+            48 => RimeFog,
+            51 => Drizzle(Light),
+            53 => Drizzle(Moderate),
+            55 => Drizzle(Heavy),
+            56 => FreezingDrizzle(Light),
+            57 => FreezingDrizzle(Heavy),
+            61 => Rain(Light),
+            63 => Rain(Moderate),
+            65 => Rain(Heavy),
+            66 => FreezingRain(Light),
+            67 => FreezingRain(Heavy),
+            71 => Snow(Light),
+            73 => Snow(Moderate),
+            75 => Snow(Heavy),
+            77 => SnowGrains,
+            80 => RainShowers(Light),
+            81 => RainShowers(Moderate),
+            82 => RainShowers(Heavy),
+            85 => SnowShowers(Light),
+            86 => SnowShowers(Heavy),
+            95 => Thunderstorm,
+            96 => ThunderstormWithHail(Light),
+            99 => ThunderstormWithHail(Heavy),
+            _ => return None,
+        };
+
+        Some(condition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Intensity, WeatherCondition};
+
+    #[test]
+    fn from_code_interprets_wmo_codes() {
+        assert_eq!(
+            WeatherCondition::from_code(0),
+            Some(WeatherCondition::Clear)
+        );
+        assert_eq!(
+            WeatherCondition::from_code(65),
+            Some(WeatherCondition::Rain(Intensity::Heavy))
+        );
+        assert_eq!(
+            WeatherCondition::from_code(86),
+            Some(WeatherCondition::SnowShowers(Intensity::Heavy))
+        );
+        assert_eq!(
+            WeatherCondition::from_code(96),
+            Some(WeatherCondition::ThunderstormWithHail(Intensity::Light))
+        );
+        assert_eq!(WeatherCondition::from_code(4), None);
+        assert_eq!(WeatherCondition::from_code(100), None);
+    }
+}
@@ -1,22 +1,193 @@
+pub mod condition;
 pub mod weather_api;
 
 use super::config::Config;
 use super::geolocation::Coordinates;
+use crate::client::query::{extend_list_param, parse_url, set_param};
+use crate::client::Client;
 use crate::config::args::{TemperatureUnit, WindspeedUnit};
+use crate::errors::CustomError;
+use chrono::{DateTime, FixedOffset, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use condition::WeatherCondition;
+use reqwest::Url;
+use std::time::Duration;
 use weather_api::response_handler;
 
-/// A struct representing the current weather using `temperature`, `windspeed`, `is_day` and `timestamp`.
+/// A struct representing a temperature `value` in the `unit` it was requested in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Temperature {
+    pub value: f64,
+    pub unit: TemperatureUnit,
+}
+
+impl Temperature {
+    /// Returns the temperature converted to `unit`.
+    pub fn to(self, unit: TemperatureUnit) -> Temperature {
+        let celsius = match self.unit {
+            TemperatureUnit::Celsius => self.value,
+            TemperatureUnit::Fahrenheit => (self.value - 32.0) * 5.0 / 9.0,
+        };
+
+        let value = match unit {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        };
+
+        Temperature { value, unit }
+    }
+}
+
+/// A struct representing a wind speed `value` in the `unit` it was requested in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindSpeed {
+    pub value: f64,
+    pub unit: WindspeedUnit,
+}
+
+impl WindSpeed {
+    /// Returns the wind speed converted to `unit`.
+    pub fn to(self, unit: WindspeedUnit) -> WindSpeed {
+        let meters_per_second = self.value / meters_per_second_factor(self.unit);
+
+        WindSpeed {
+            value: meters_per_second * meters_per_second_factor(unit),
+            unit,
+        }
+    }
+
+    /// Returns the Beaufort number of the wind speed, from 0 (calm) to 12 (hurricane force).
+    pub fn beaufort(self) -> u8 {
+        let meters_per_second = self.to(WindspeedUnit::Ms).value;
+
+        BEAUFORT_LIMITS
+            .iter()
+            .filter(|limit| meters_per_second >= **limit)
+            .count() as u8
+    }
+}
+
+/// The wind speeds in m/s at which the Beaufort numbers 1 to 12 begin.
+const BEAUFORT_LIMITS: [f64; 12] = [
+    0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+];
+
+/// A struct representing the direction the wind blows from in `degrees` clockwise from north.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindDirection {
+    pub degrees: u16,
+}
+
+impl WindDirection {
+    /// Returns the nearest of the 16 compass points, counted clockwise from 0 for north,
+    /// e.g. 4 for east and 15 for north-northwest.
+    pub fn compass_point(self) -> usize {
+        (f64::from(self.degrees % 360) / 22.5).round() as usize % 16
+    }
+}
+
+/// The timezone of a location: its IANA zone if the API named one, so times after a change
+/// to or from daylight saving time are shown correctly, else the fixed offset the API sent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Returns the offset to UTC in effect at `date_time`.
+    pub fn offset_at(&self, date_time: &DateTime<Utc>) -> FixedOffset {
+        match self {
+            Zone::Named(tz) => tz.offset_from_utc_datetime(&date_time.naive_utc()).fix(),
+            Zone::Fixed(offset) => *offset,
+        }
+    }
+}
+
+/// Returns how many of `unit` make up one meter per second.
+fn meters_per_second_factor(unit: WindspeedUnit) -> f64 {
+    match unit {
+        WindspeedUnit::Kmh => 3.6,
+        WindspeedUnit::Ms => 1.0,
+        WindspeedUnit::Mph => 3600.0 / 1609.344,
+        WindspeedUnit::Kn => 3600.0 / 1852.0,
+    }
+}
+
+/// A struct representing the current weather using `temperature`, `windspeed`, `winddirection`,
+/// the `condition` (e.g. heavy rain), `is_day`, `timestamp`, the `zone` of the location
+/// and its `timezone` name.
+/// Values that were not requested or the API has no data for are `None`. The `humidity` and
+/// `cloudcover` are percentages, the `surface_pressure` is in hectopascals, the `precipitation`
+/// of the preceding interval in millimeters and the `visibility` in meters.
+/// `stale_age` is set if the weather is a stored response shown because the API could not be reached.
 pub struct CurrentWeather {
-    pub temperature: String,
-    pub windspeed: String,
-    pub is_day: String,
-    pub timestamp: u64,
+    pub temperature: Temperature,
+    pub apparent_temperature: Option<Temperature>,
+    pub humidity: Option<u8>,
+    pub surface_pressure: Option<f64>,
+    pub precipitation: Option<f64>,
+    pub cloudcover: Option<u8>,
+    pub visibility: Option<f64>,
+    pub windspeed: WindSpeed,
+    pub winddirection: Option<WindDirection>,
+    pub windgusts: Option<WindSpeed>,
+    pub uv_index: Option<f64>,
+    pub condition: Option<WeatherCondition>,
+    pub is_day: bool,
+    pub timestamp: DateTime<Utc>,
+    pub zone: Zone,
+    pub timezone: String,
+    pub stale_age: Option<Duration>,
+}
+
+/// A struct representing a daily forecast using the `zone` of the location and a list of `days`.
+/// `stale_age` is set if the forecast is a stored response shown because the API could not be reached.
+pub struct DailyForecast {
+    pub zone: Zone,
+    pub days: Vec<ForecastDay>,
+    pub stale_age: Option<Duration>,
+}
+
+/// A struct representing the forecast of a single day.
+/// Values the API has no data for are `None`, the `precipitation_sum` is in millimeters.
+pub struct ForecastDay {
+    pub timestamp: DateTime<Utc>,
+    pub condition: Option<WeatherCondition>,
+    pub temperature_max: Option<Temperature>,
+    pub temperature_min: Option<Temperature>,
+    pub precipitation_sum: Option<f64>,
+    pub windspeed_max: Option<WindSpeed>,
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+}
+
+/// A struct representing an hourly forecast using the `zone` of the location and a list of `hours`.
+/// `stale_age` is set if the forecast is a stored response shown because the API could not be reached.
+pub struct HourlyForecast {
+    pub zone: Zone,
+    pub hours: Vec<ForecastHour>,
+    pub stale_age: Option<Duration>,
+}
+
+/// A struct representing the forecast of a single hour.
+/// Values the API has no data for are `None`, percentages range from 0 to 100.
+pub struct ForecastHour {
+    pub timestamp: DateTime<Utc>,
+    pub temperature: Option<Temperature>,
+    pub precipitation_probability: Option<u8>,
+    pub windspeed: Option<WindSpeed>,
+    pub winddirection: Option<WindDirection>,
+    pub cloudcover: Option<u8>,
 }
 
-/// Asynchronously retrieves location information for a given location string.
+/// Asynchronously retrieves the current weather for the given coordinates.
 ///
 /// # Arguments
-/// * `location`: The location for which to retrieve information.
+/// * `client`: The HTTP client used for the request.
+/// * `coordinates`: The coordinates for which to retrieve the weather.
+/// * `temperature_unit`: The unit the temperature is returned in.
+/// * `windspeed_unit`: The unit the wind speed is returned in.
 ///
 /// # Returns
 /// Returns a `Result` containing a `CurrentWeather` instance if successful,
@@ -28,70 +199,222 @@ pub struct CurrentWeather {
 /// * The HTTP request to the Weather API fails.
 /// * Deserialization of the API response into a `CurrentWeather` struct fails.
 pub async fn get_info_for(
+    client: &Client,
     coordinates: &Coordinates,
     temperature_unit: &TemperatureUnit,
     windspeed_unit: &WindspeedUnit,
-) -> Result<CurrentWeather, Box<dyn std::error::Error>> {
-    let base_url = Config::get_value("weather_api_url")?;
-    let mut url_unmodified = WeatherApiUrl::new(base_url);
+) -> Result<CurrentWeather, CustomError> {
+    let base_url = Config::get_api_url("weather_api_url")?;
+    let mut url_unmodified = WeatherApiUrl::new(&base_url)?;
 
-    let url = &url_unmodified
-        .set_coordinates(&coordinates.latitude, &coordinates.longitude)?
+    // Templates still using the legacy `current_weather=true` get the `current` block as well.
+    let url = url_unmodified
+        .set_coordinates(coordinates)?
         .set_temperature_unit(temperature_unit)?
         .set_windspeed_unit(windspeed_unit)?
-        .url;
+        .add_variables("current", &CURRENT_VARIABLES)?
+        .url
+        .as_str();
 
-    let weather_info = response_handler::deserialize(weather_api::Client::fetch(url).await)?;
+    let fetched = client.fetch_cached(url, weather_ttl()?).await?;
+    let mut weather_info =
+        response_handler::deserialize(Ok(fetched.body), temperature_unit, windspeed_unit)?;
+    weather_info.stale_age = fetched.stale_age;
 
     Ok(weather_info)
 }
 
+/// Asynchronously retrieves the daily forecast for the given coordinates.
+///
+/// # Arguments
+/// * `client`: The HTTP client used for the request.
+/// * `coordinates`: The coordinates for which to retrieve the forecast.
+/// * `days`: The number of days to include in the forecast, starting today.
+///
+/// # Returns
+/// Returns a `Result` containing a `DailyForecast` instance if successful,
+/// or an error if the retrieval fails, the URL is invalid, or the API response is malformed.
+///
+/// # Errors
+/// This function can return errors in the following scenarios:
+/// * The URL construction or modification fails.
+/// * The HTTP request to the Weather API fails.
+/// * Deserialization of the API response into a `DailyForecast` struct fails.
+pub async fn get_daily_forecast_for(
+    client: &Client,
+    coordinates: &Coordinates,
+    temperature_unit: &TemperatureUnit,
+    windspeed_unit: &WindspeedUnit,
+    days: u8,
+) -> Result<DailyForecast, CustomError> {
+    let base_url = Config::get_api_url("daily_weather_api_url")?;
+    let mut url_unmodified = WeatherApiUrl::new(&base_url)?;
+
+    let url = url_unmodified
+        .set_coordinates(coordinates)?
+        .set_temperature_unit(temperature_unit)?
+        .set_windspeed_unit(windspeed_unit)?
+        .set_forecast_days(days)?
+        .url
+        .as_str();
+
+    let fetched = client.fetch_cached(url, weather_ttl()?).await?;
+    let mut forecast =
+        response_handler::deserialize_daily(Ok(fetched.body), temperature_unit, windspeed_unit)?;
+    forecast.stale_age = fetched.stale_age;
+
+    Ok(forecast)
+}
+
+/// Asynchronously retrieves the hourly forecast for the given coordinates.
+///
+/// # Arguments
+/// * `client`: The HTTP client used for the request.
+/// * `coordinates`: The coordinates for which to retrieve the forecast.
+/// * `hours`: The number of hours to include in the forecast, starting with the current hour.
+///
+/// # Returns
+/// Returns a `Result` containing a `HourlyForecast` instance if successful,
+/// or an error if the retrieval fails, the URL is invalid, or the API response is malformed.
+///
+/// # Errors
+/// This function can return errors in the following scenarios:
+/// * The URL construction or modification fails.
+/// * The HTTP request to the Weather API fails.
+/// * Deserialization of the API response into a `HourlyForecast` struct fails.
+pub async fn get_hourly_forecast_for(
+    client: &Client,
+    coordinates: &Coordinates,
+    temperature_unit: &TemperatureUnit,
+    windspeed_unit: &WindspeedUnit,
+    hours: u8,
+) -> Result<HourlyForecast, CustomError> {
+    let base_url = Config::get_api_url("hourly_weather_api_url")?;
+    let mut url_unmodified = WeatherApiUrl::new(&base_url)?;
+
+    let url = url_unmodified
+        .set_coordinates(coordinates)?
+        .set_temperature_unit(temperature_unit)?
+        .set_windspeed_unit(windspeed_unit)?
+        .set_forecast_hours(hours)?
+        .url
+        .as_str();
+
+    let fetched = client.fetch_cached(url, weather_ttl()?).await?;
+    let mut forecast =
+        response_handler::deserialize_hourly(Ok(fetched.body), temperature_unit, windspeed_unit)?;
+    forecast.stale_age = fetched.stale_age;
+
+    Ok(forecast)
+}
+
+/// The variables of the `current` block, requested for every report row whichever `fields`
+/// are shown, so a cached response serves any of them.
+const CURRENT_VARIABLES: [&str; 13] = [
+    "temperature_2m",
+    "windspeed_10m",
+    "winddirection_10m",
+    "weathercode",
+    "is_day",
+    "apparent_temperature",
+    "relativehumidity_2m",
+    "surface_pressure",
+    "precipitation",
+    "cloudcover",
+    "visibility",
+    "windgusts_10m",
+    "uv_index",
+];
+
+/// Returns how long cached weather responses stay valid.
+fn weather_ttl() -> Result<Duration, CustomError> {
+    Ok(Duration::from_secs(Config::get_number(
+        "cache_ttl_weather_seconds",
+    )?))
+}
+
 /// A struct representing the `url` of the weather API.
-/// Placeholders in the `url` get replaced using setters.
+/// Query parameters of the `url` get set using setters, which percent-encode their values.
 pub struct WeatherApiUrl {
-    pub url: String,
+    pub url: Url,
 }
 
 impl WeatherApiUrl {
-    /// Instantiate a new `WeatherApiUrl` instance.
-    pub fn new(base_url: String) -> Self {
-        WeatherApiUrl { url: base_url }
+    /// Instantiate a new `WeatherApiUrl` instance from the configured URL.
+    ///
+    /// # Errors
+    /// * `base_url` is not a valid absolute URL.
+    pub fn new(base_url: &str) -> Result<Self, CustomError> {
+        Ok(WeatherApiUrl {
+            url: parse_url(base_url)?,
+        })
     }
 
-    /// Replaces the coordinates placeholder in the Weather API URL with values.
+    /// Sets the coordinates in the Weather API URL.
     pub fn set_coordinates(
         &mut self,
-        latitude: &str,
-        longitude: &str,
-    ) -> Result<&mut WeatherApiUrl, Box<dyn std::error::Error>> {
-        self.url = self
-            .url
-            .replace("__LAT__", latitude)
-            .replace("__LON__", longitude);
+        coordinates: &Coordinates,
+    ) -> Result<&mut WeatherApiUrl, CustomError> {
+        set_param(
+            &mut self.url,
+            "latitude",
+            &coordinates.latitude().to_string(),
+        );
+        set_param(
+            &mut self.url,
+            "longitude",
+            &coordinates.longitude().to_string(),
+        );
 
         Ok(self)
     }
 
-    /// Replaces the temperature unit placeholder in the Weather API URL with a value.
+    /// Sets the temperature unit in the Weather API URL.
     pub fn set_temperature_unit(
         &mut self,
         temperature_unit: &TemperatureUnit,
-    ) -> Result<&mut WeatherApiUrl, Box<dyn std::error::Error>> {
-        self.url = self
-            .url
-            .replace("__TEMPERATURE_UNIT__", &temperature_unit.to_string());
+    ) -> Result<&mut WeatherApiUrl, CustomError> {
+        set_param(
+            &mut self.url,
+            "temperature_unit",
+            &temperature_unit.to_string(),
+        );
 
         Ok(self)
     }
 
-    /// Replaces the wind speed unit placeholder in the Weather API URL with a value.
+    /// Sets the wind speed unit in the Weather API URL.
     pub fn set_windspeed_unit(
         &mut self,
         windspeed_unit: &WindspeedUnit,
-    ) -> Result<&mut WeatherApiUrl, Box<dyn std::error::Error>> {
-        self.url = self
-            .url
-            .replace("__WINDSPEED_UNIT__", &windspeed_unit.to_string());
+    ) -> Result<&mut WeatherApiUrl, CustomError> {
+        set_param(&mut self.url, "windspeed_unit", &windspeed_unit.to_string());
+
+        Ok(self)
+    }
+
+    /// Sets the number of forecast days in the Weather API URL.
+    pub fn set_forecast_days(&mut self, days: u8) -> Result<&mut WeatherApiUrl, CustomError> {
+        set_param(&mut self.url, "forecast_days", &days.to_string());
+
+        Ok(self)
+    }
+
+    /// Sets the number of forecast hours in the Weather API URL.
+    pub fn set_forecast_hours(&mut self, hours: u8) -> Result<&mut WeatherApiUrl, CustomError> {
+        set_param(&mut self.url, "forecast_hours", &hours.to_string());
+
+        Ok(self)
+    }
+
+    /// Requests additional weather variables in a block of the Weather API URL,
+    /// e.g. `relativehumidity_2m` in `hourly`.
+    pub fn add_variables(
+        &mut self,
+        block: &str,
+        variables: &[&str],
+    ) -> Result<&mut WeatherApiUrl, CustomError> {
+        extend_list_param(&mut self.url, block, variables);
 
         Ok(self)
     }
@@ -99,56 +422,140 @@ impl WeatherApiUrl {
 
 #[cfg(test)]
 mod tests {
+    use crate::client::{Client, ClientSettings};
     use crate::config::args::{TemperatureUnit, WindspeedUnit};
     use crate::config::Config;
     use crate::geolocation::Coordinates;
     use crate::weather;
-    use crate::weather::WeatherApiUrl;
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use crate::weather::{Temperature, WeatherApiUrl, WindDirection, WindSpeed};
+    use chrono::Utc;
     use weather::get_info_for;
 
     #[test]
     fn setters_insert_correct_information_into_url() {
-        let mut weather_api_url = WeatherApiUrl::new(Config::get_value("weather_api_url").unwrap());
+        let mut weather_api_url =
+            WeatherApiUrl::new(&Config::get_value("weather_api_url").unwrap()).unwrap();
 
-        let actual_url = &weather_api_url
-            .set_coordinates("40.71427", "-74.00597")
+        let actual_url = weather_api_url
+            .set_coordinates(&Coordinates::new(40.71427, -74.00597).unwrap())
             .unwrap()
             .set_temperature_unit(&TemperatureUnit::Celsius)
             .unwrap()
             .set_windspeed_unit(&WindspeedUnit::Kmh)
             .unwrap()
-            .url;
+            .url
+            .as_str();
 
         assert!(actual_url.contains("latitude=40.71427&longitude=-74.00597"));
         assert!(actual_url.contains("temperature_unit=celsius"));
         assert!(actual_url.contains("windspeed_unit=kmh"));
     }
 
+    #[test]
+    fn set_forecast_days_inserts_days_into_daily_url() {
+        let mut weather_api_url =
+            WeatherApiUrl::new(&Config::get_value("daily_weather_api_url").unwrap()).unwrap();
+
+        let actual_url = weather_api_url.set_forecast_days(7).unwrap().url.as_str();
+
+        assert!(actual_url.contains("forecast_days=7"));
+    }
+
+    #[test]
+    fn set_forecast_hours_inserts_hours_into_hourly_url() {
+        let mut weather_api_url =
+            WeatherApiUrl::new(&Config::get_value("hourly_weather_api_url").unwrap()).unwrap();
+
+        let actual_url = weather_api_url.set_forecast_hours(12).unwrap().url.as_str();
+
+        assert!(actual_url.contains("forecast_hours=12"));
+    }
+
+    #[test]
+    fn add_variables_extends_requested_block() {
+        let mut weather_api_url =
+            WeatherApiUrl::new(&Config::get_value("hourly_weather_api_url").unwrap()).unwrap();
+
+        let actual_url = weather_api_url
+            .add_variables("hourly", &["relativehumidity_2m"])
+            .unwrap()
+            .url
+            .as_str();
+
+        assert!(actual_url.contains("cloudcover%2Crelativehumidity_2m&timezone"));
+    }
+
     #[tokio::test]
     async fn get_info_for_fetches_required_weather_information() {
+        // Don't write into the user's cache directory.
+        let mut settings = ClientSettings::from_config().unwrap();
+        settings.cache_dir = None;
+        let client = Client::new(settings).unwrap();
+
         let result = get_info_for(
-            &Coordinates {
-                latitude: "40.71427".to_string(),
-                longitude: "-74.00597".to_string(),
-            },
+            &client,
+            &Coordinates::new(40.71427, -74.00597).unwrap(),
             &TemperatureUnit::Celsius,
             &WindspeedUnit::Kmh,
         )
         .await
         .unwrap();
 
-        assert!(!result.temperature.is_empty());
-        assert!(!result.windspeed.is_empty());
-        assert!(!result.is_day.is_empty());
-        assert!(result.timestamp > 0);
-        assert!(result.timestamp < current_timestamp());
+        assert!(result.temperature.value > -100.0);
+        assert!(result.humidity.is_some());
+        assert_eq!(result.temperature.unit, TemperatureUnit::Celsius);
+        assert!(result.windspeed.value >= 0.0);
+        assert!(result.timestamp < Utc::now());
+    }
+
+    #[test]
+    fn temperature_converts_between_units() {
+        let boiling = Temperature {
+            value: 100.0,
+            unit: TemperatureUnit::Celsius,
+        };
+
+        let fahrenheit = boiling.to(TemperatureUnit::Fahrenheit);
+
+        assert_eq!(fahrenheit.value, 212.0);
+        assert_eq!(fahrenheit.unit, TemperatureUnit::Fahrenheit);
+        assert_eq!(fahrenheit.to(TemperatureUnit::Celsius), boiling);
+    }
+
+    #[test]
+    fn windspeed_converts_between_units() {
+        let wind = WindSpeed {
+            value: 36.0,
+            unit: WindspeedUnit::Kmh,
+        };
+
+        assert!((wind.to(WindspeedUnit::Ms).value - 10.0).abs() < 1e-9);
+        assert!((wind.to(WindspeedUnit::Kn).value - 19.438).abs() < 1e-3);
+        assert!((wind.to(WindspeedUnit::Mph).value - 22.369).abs() < 1e-3);
+    }
+
+    #[test]
+    fn windspeed_is_classified_on_the_beaufort_scale() {
+        let beaufort = |value, unit| WindSpeed { value, unit }.beaufort();
+
+        assert_eq!(beaufort(0.2, WindspeedUnit::Ms), 0);
+        assert_eq!(beaufort(11.3, WindspeedUnit::Kmh), 2);
+        assert_eq!(beaufort(8.0, WindspeedUnit::Ms), 5);
+        assert_eq!(beaufort(34.0, WindspeedUnit::Kn), 8);
+        assert_eq!(beaufort(120.0, WindspeedUnit::Kmh), 12);
     }
 
-    fn current_timestamp() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("System Time before UNIX EPOCH.")
-            .as_secs()
+    #[test]
+    fn wind_direction_rounds_to_nearest_compass_point() {
+        let point = |degrees| WindDirection { degrees }.compass_point();
+
+        assert_eq!(point(0), 0);
+        assert_eq!(point(11), 0);
+        assert_eq!(point(12), 1);
+        assert_eq!(point(90), 4);
+        assert_eq!(point(230), 10);
+        assert_eq!(point(340), 15);
+        assert_eq!(point(355), 0);
+        assert_eq!(point(360), 0);
     }
 }
@@ -1,11 +1,82 @@
-use super::super::CurrentWeather;
+use super::super::condition::WeatherCondition;
+use super::super::{
+    CurrentWeather, DailyForecast, ForecastDay, ForecastHour, HourlyForecast, Temperature,
+    WindDirection, WindSpeed, Zone,
+};
+use crate::config::args::{TemperatureUnit, WindspeedUnit};
 use crate::errors::CustomError;
+use chrono::{DateTime, FixedOffset, Offset, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use serde_json::Value;
 
+/// The fields describing the location in every response of the weather API,
+/// see <https://open-meteo.com/en/docs>.
+#[derive(Deserialize)]
+struct Header {
+    #[serde(default)]
+    utc_offset_seconds: i32,
+    #[serde(default)]
+    timezone: String,
+}
+
+/// The `current` block of a response. The aliases read the legacy `current_weather` block,
+/// which lacks the optional variables.
+/// Whole-numbered variables are read as `f64` too, as the API may send them as e.g. `230.0`.
+#[derive(Deserialize)]
+struct CurrentBlock {
+    time: i64,
+    #[serde(alias = "temperature")]
+    temperature_2m: f64,
+    apparent_temperature: Option<f64>,
+    relativehumidity_2m: Option<f64>,
+    surface_pressure: Option<f64>,
+    precipitation: Option<f64>,
+    cloudcover: Option<f64>,
+    visibility: Option<f64>,
+    #[serde(alias = "windspeed")]
+    windspeed_10m: f64,
+    #[serde(alias = "winddirection")]
+    winddirection_10m: Option<f64>,
+    windgusts_10m: Option<f64>,
+    uv_index: Option<f64>,
+    weathercode: Option<f64>,
+    is_day: f64,
+}
+
+/// The `daily` block of a response with one series per variable.
+/// Values the API has no data for are `null`. Codes are read as `f64` like in `CurrentBlock`.
+#[derive(Deserialize)]
+struct DailyBlock {
+    time: Vec<i64>,
+    weathercode: Vec<Option<f64>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    precipitation_sum: Vec<Option<f64>>,
+    windspeed_10m_max: Vec<Option<f64>>,
+    sunrise: Vec<Option<i64>>,
+    sunset: Vec<Option<i64>>,
+}
+
+/// The `hourly` block of a response with one series per variable.
+/// Values the API has no data for are `null`. Whole-numbered variables are read as `f64`
+/// like in `CurrentBlock`.
+#[derive(Deserialize)]
+struct HourlyBlock {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f64>>,
+    precipitation_probability: Vec<Option<f64>>,
+    windspeed_10m: Vec<Option<f64>>,
+    winddirection_10m: Vec<Option<f64>>,
+    cloudcover: Vec<Option<f64>>,
+}
+
 /// Deserializes a JSON string into a `CurrentWeather` struct.
 ///
 /// # Arguments
 /// * `body`: A `Result` containing a JSON string or an error.
+/// * `temperature_unit`: The unit the temperature was requested in.
+/// * `windspeed_unit`: The unit the wind speed was requested in.
 ///
 /// # Returns
 /// Returns a `Result` containing a `CurrentWeather` instance if deserialization is successful,
@@ -15,64 +86,476 @@ use serde_json::Value;
 /// This function can return errors in the following scenarios:
 /// * The JSON string cannot be parsed.
 /// * An error field is found in the JSON structure.
+/// * Neither `current` nor `current_weather` is present.
+/// * A required field of the block is missing or has the wrong type.
 pub fn deserialize(
-    body: Result<String, Box<dyn std::error::Error>>,
-) -> Result<CurrentWeather, Box<dyn std::error::Error>> {
-    let parsed_body: Value =
-        serde_json::from_str(&body?).map_err(|err| format!("Error parsing JSON: {}", err))?;
+    body: Result<String, CustomError>,
+    temperature_unit: &TemperatureUnit,
+    windspeed_unit: &WindspeedUnit,
+) -> Result<CurrentWeather, CustomError> {
+    let parsed_body: Value = serde_json::from_str(&body?)?;
 
-    if parsed_body["error"] == true {
-        return Err(Box::new(CustomError::WeatherInfoNotFound(
-            "current_weather".to_string(),
-        )));
-    }
+    check_api_error(&parsed_body)?;
+
+    let header = header(&parsed_body)?;
+    // Templates may still request the legacy block with `current_weather=true`.
+    let name = if parsed_body.get("current").is_some() {
+        "current"
+    } else {
+        "current_weather"
+    };
+    let current: CurrentBlock = block(&parsed_body, name)?;
 
-    let temperature = &parsed_body["current_weather"]["temperature"]
-        .as_f64()
-        .unwrap();
-    let windspeed = &parsed_body["current_weather"]["windspeed"]
-        .as_f64()
-        .unwrap();
-    let is_day = &parsed_body["current_weather"]["is_day"].as_u64().unwrap();
-    let unix_timestamp = &parsed_body["current_weather"]["time"].as_u64().unwrap();
+    let temperature = |value| Temperature {
+        value,
+        unit: *temperature_unit,
+    };
+    let windspeed = |value| WindSpeed {
+        value,
+        unit: *windspeed_unit,
+    };
 
     let current_weather = CurrentWeather {
-        temperature: temperature.to_string(),
-        windspeed: windspeed.to_string(),
-        is_day: is_day.to_string(),
-        timestamp: *unix_timestamp,
+        temperature: temperature(current.temperature_2m),
+        apparent_temperature: current.apparent_temperature.map(temperature),
+        humidity: current.relativehumidity_2m.map(percent),
+        surface_pressure: current.surface_pressure,
+        precipitation: current.precipitation,
+        cloudcover: current.cloudcover.map(percent),
+        visibility: current.visibility,
+        windspeed: windspeed(current.windspeed_10m),
+        winddirection: current.winddirection_10m.map(|value| WindDirection {
+            degrees: degrees(value),
+        }),
+        windgusts: current.windgusts_10m.map(windspeed),
+        uv_index: current.uv_index,
+        condition: current
+            .weathercode
+            .and_then(|value| WeatherCondition::from_code(whole(value)?)),
+        is_day: current.is_day != 0.0,
+        timestamp: date_time(&format!("{}.time", name), current.time)?,
+        zone: zone(&header),
+        timezone: header.timezone,
+        stale_age: None,
     };
 
     Ok(current_weather)
 }
 
+/// Deserializes a JSON string into a `DailyForecast` struct.
+///
+/// # Arguments
+/// * `body`: A `Result` containing a JSON string or an error.
+/// * `temperature_unit`: The unit the temperatures were requested in.
+/// * `windspeed_unit`: The unit the wind speeds were requested in.
+///
+/// # Returns
+/// Returns a `Result` containing a `DailyForecast` instance if deserialization is successful,
+/// or an error if the JSON structure is invalid or an API error exists.
+///
+/// # Errors
+/// This function can return errors in the following scenarios:
+/// * The JSON string cannot be parsed.
+/// * An error field is found in the JSON structure.
+/// * The `daily` field or one of its series is missing or has the wrong type.
+pub fn deserialize_daily(
+    body: Result<String, CustomError>,
+    temperature_unit: &TemperatureUnit,
+    windspeed_unit: &WindspeedUnit,
+) -> Result<DailyForecast, CustomError> {
+    let parsed_body: Value = serde_json::from_str(&body?)?;
+
+    check_api_error(&parsed_body)?;
+
+    let header = header(&parsed_body)?;
+    let daily: DailyBlock = block(&parsed_body, "daily")?;
+
+    let temperature = |value| Temperature {
+        value,
+        unit: *temperature_unit,
+    };
+    let windspeed = |value| WindSpeed {
+        value,
+        unit: *windspeed_unit,
+    };
+
+    let days = daily
+        .time
+        .iter()
+        .enumerate()
+        .map(|(i, time)| {
+            Ok(ForecastDay {
+                timestamp: date_time("daily.time", *time)?,
+                condition: at(&daily.weathercode, i)
+                    .and_then(|value| WeatherCondition::from_code(whole(value)?)),
+                temperature_max: at(&daily.temperature_2m_max, i).map(temperature),
+                temperature_min: at(&daily.temperature_2m_min, i).map(temperature),
+                precipitation_sum: at(&daily.precipitation_sum, i),
+                windspeed_max: at(&daily.windspeed_10m_max, i).map(windspeed),
+                sunrise: at(&daily.sunrise, i).and_then(|time| DateTime::from_timestamp(time, 0)),
+                sunset: at(&daily.sunset, i).and_then(|time| DateTime::from_timestamp(time, 0)),
+            })
+        })
+        .collect::<Result<Vec<ForecastDay>, CustomError>>()?;
+
+    let forecast = DailyForecast {
+        zone: zone(&header),
+        days,
+        stale_age: None,
+    };
+
+    Ok(forecast)
+}
+
+/// Deserializes a JSON string into a `HourlyForecast` struct.
+///
+/// # Arguments
+/// * `body`: A `Result` containing a JSON string or an error.
+/// * `temperature_unit`: The unit the temperatures were requested in.
+/// * `windspeed_unit`: The unit the wind speeds were requested in.
+///
+/// # Returns
+/// Returns a `Result` containing a `HourlyForecast` instance if deserialization is successful,
+/// or an error if the JSON structure is invalid or an API error exists.
+///
+/// # Errors
+/// This function can return errors in the following scenarios:
+/// * The JSON string cannot be parsed.
+/// * An error field is found in the JSON structure.
+/// * The `hourly` field or one of its series is missing or has the wrong type.
+pub fn deserialize_hourly(
+    body: Result<String, CustomError>,
+    temperature_unit: &TemperatureUnit,
+    windspeed_unit: &WindspeedUnit,
+) -> Result<HourlyForecast, CustomError> {
+    let parsed_body: Value = serde_json::from_str(&body?)?;
+
+    check_api_error(&parsed_body)?;
+
+    let header = header(&parsed_body)?;
+    let hourly: HourlyBlock = block(&parsed_body, "hourly")?;
+
+    let hours = hourly
+        .time
+        .iter()
+        .enumerate()
+        .map(|(i, time)| {
+            Ok(ForecastHour {
+                timestamp: date_time("hourly.time", *time)?,
+                temperature: at(&hourly.temperature_2m, i).map(|value| Temperature {
+                    value,
+                    unit: *temperature_unit,
+                }),
+                precipitation_probability: at(&hourly.precipitation_probability, i).map(percent),
+                windspeed: at(&hourly.windspeed_10m, i).map(|value| WindSpeed {
+                    value,
+                    unit: *windspeed_unit,
+                }),
+                winddirection: at(&hourly.winddirection_10m, i).map(|value| WindDirection {
+                    degrees: degrees(value),
+                }),
+                cloudcover: at(&hourly.cloudcover, i).map(percent),
+            })
+        })
+        .collect::<Result<Vec<ForecastHour>, CustomError>>()?;
+
+    Ok(HourlyForecast {
+        zone: zone(&header),
+        hours,
+        stale_age: None,
+    })
+}
+
+fn header(parsed_body: &Value) -> Result<Header, CustomError> {
+    Header::deserialize(parsed_body).map_err(|err| CustomError::MalformedJson(err.to_string()))
+}
+
+/// Deserializes the `block` object of a response, e.g. `daily` or `hourly`.
+fn block<T: DeserializeOwned>(parsed_body: &Value, block: &str) -> Result<T, CustomError> {
+    let value = parsed_body
+        .get(block)
+        .filter(|value| value.is_object())
+        .ok_or_else(|| CustomError::MissingField(block.to_string()))?;
+
+    T::deserialize(value).map_err(|err| CustomError::MissingField(format!("{}: {}", block, err)))
+}
+
+/// Returns the `i`-th value of a series, or `None` if the API has no data for it.
+fn at<T: Copy>(series: &[Option<T>], i: usize) -> Option<T> {
+    series.get(i).copied().flatten()
+}
+
+/// Rounds a percentage to a whole percent between 0 and 100.
+fn percent(value: f64) -> u8 {
+    value.round().clamp(0.0, 100.0) as u8
+}
+
+/// Rounds a direction to whole degrees between 0 and 359.
+fn degrees(value: f64) -> u16 {
+    value.round().rem_euclid(360.0) as u16
+}
+
+/// Returns a code sent as a number, e.g. `3.0`, or `None` if it is no whole number a code can be.
+fn whole(value: f64) -> Option<u8> {
+    (value.fract() == 0.0 && (0.0..=f64::from(u8::MAX)).contains(&value)).then_some(value as u8)
+}
+
+/// Converts the unix timestamp of the field `key` into a date and time.
+fn date_time(key: &str, timestamp: i64) -> Result<DateTime<Utc>, CustomError> {
+    DateTime::from_timestamp(timestamp, 0).ok_or_else(|| {
+        CustomError::MissingField(format!("{}: {} is not a valid time", key, timestamp))
+    })
+}
+
+/// Returns the zone the API named for the location. Without a known name it falls back to the
+/// offset to UTC the API sent, or UTC itself if that is invalid.
+fn zone(header: &Header) -> Zone {
+    match header.timezone.parse() {
+        Ok(tz) => Zone::Named(tz),
+        Err(_) => {
+            Zone::Fixed(FixedOffset::east_opt(header.utc_offset_seconds).unwrap_or(Utc.fix()))
+        }
+    }
+}
+
+/// Returns the API error if the weather API reported one via its `error` field.
+fn check_api_error(parsed_body: &Value) -> Result<(), CustomError> {
+    if parsed_body["error"] == true {
+        return Err(CustomError::Api(
+            parsed_body["reason"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::deserialize;
+    use super::{deserialize, deserialize_daily, deserialize_hourly};
+    use crate::config::args::{TemperatureUnit, WindspeedUnit};
+    use crate::errors::CustomError;
+    use crate::weather::condition::{Intensity, WeatherCondition};
+    use crate::weather::{Temperature, WindDirection, WindSpeed, Zone};
+    use chrono::{DateTime, FixedOffset};
+    use chrono_tz::Tz;
+
+    const UNITS: (&TemperatureUnit, &WindspeedUnit) =
+        (&TemperatureUnit::Celsius, &WindspeedUnit::Kmh);
+
+    fn celsius(value: f64) -> Option<Temperature> {
+        Some(Temperature {
+            value,
+            unit: TemperatureUnit::Celsius,
+        })
+    }
+
+    fn kmh(value: f64) -> Option<WindSpeed> {
+        Some(WindSpeed {
+            value,
+            unit: WindspeedUnit::Kmh,
+        })
+    }
 
     #[test]
     fn deserialize_creates_correct_current_weather_struct() {
         let json =
-            r#"{"current_weather":{"time":1702740600,"temperature":8.8,"windspeed":12.7,"is_day":1}}"#.to_string();
+            r#"{"utc_offset_seconds":-18000,"timezone":"America/New_York","current":{"time":1702740600,"interval":900,"temperature_2m":8.8,"apparent_temperature":5.9,"relativehumidity_2m":71,"surface_pressure":1012.4,"precipitation":0.2,"cloudcover":100,"visibility":24140.0,"windspeed_10m":12.7,"winddirection_10m":230,"windgusts_10m":27.4,"uv_index":null,"weathercode":3,"is_day":1}}"#.to_string();
 
-        let result = deserialize(Ok(json)).unwrap();
+        let result = deserialize(Ok(json), UNITS.0, UNITS.1).unwrap();
 
-        assert_eq!(result.timestamp, 1702740600);
-        assert_eq!(result.temperature, "8.8");
-        assert_eq!(result.windspeed, "12.7");
-        assert_eq!(result.is_day, "1");
+        assert_eq!(result.timestamp.timestamp(), 1702740600);
+        assert_eq!(Some(result.temperature), celsius(8.8));
+        assert_eq!(result.apparent_temperature, celsius(5.9));
+        assert_eq!(result.humidity, Some(71));
+        assert_eq!(result.surface_pressure, Some(1012.4));
+        assert_eq!(result.precipitation, Some(0.2));
+        assert_eq!(result.cloudcover, Some(100));
+        assert_eq!(result.visibility, Some(24140.0));
+        assert_eq!(Some(result.windspeed), kmh(12.7));
+        assert_eq!(result.winddirection, Some(WindDirection { degrees: 230 }));
+        assert_eq!(result.windgusts, kmh(27.4));
+        assert_eq!(result.uv_index, None);
+        assert_eq!(result.condition, Some(WeatherCondition::Overcast));
+        assert!(result.is_day);
+        assert_eq!(result.zone, Zone::Named(Tz::America__New_York));
+        assert_eq!(result.timezone, "America/New_York");
+    }
+
+    #[test]
+    fn deserialize_reads_legacy_current_weather_block() {
+        let json =
+            r#"{"utc_offset_seconds":-18000,"timezone":"America/New_York","current_weather":{"time":1702740600,"temperature":8.8,"windspeed":12.7,"winddirection":230,"weathercode":3,"is_day":1}}"#.to_string();
+
+        let result = deserialize(Ok(json), UNITS.0, UNITS.1).unwrap();
+
+        assert_eq!(result.timestamp.timestamp(), 1702740600);
+        assert_eq!(Some(result.temperature), celsius(8.8));
+        assert_eq!(Some(result.windspeed), kmh(12.7));
+        assert_eq!(result.winddirection, Some(WindDirection { degrees: 230 }));
+        assert_eq!(result.condition, Some(WeatherCondition::Overcast));
+        assert!(result.is_day);
+        assert_eq!(result.zone, Zone::Named(Tz::America__New_York));
+        assert_eq!(result.timezone, "America/New_York");
+        assert_eq!(result.humidity, None);
+    }
+
+    #[test]
+    fn deserialize_reads_whole_numbers_sent_as_floats() {
+        let json =
+            r#"{"current":{"time":1702740600,"temperature_2m":8.8,"relativehumidity_2m":70.6,"cloudcover":100.0,"windspeed_10m":12.7,"winddirection_10m":359.7,"weathercode":3.0,"is_day":0.0}}"#.to_string();
+
+        let result = deserialize(Ok(json), UNITS.0, UNITS.1).unwrap();
+
+        assert_eq!(result.humidity, Some(71));
+        assert_eq!(result.cloudcover, Some(100));
+        assert_eq!(result.winddirection, Some(WindDirection { degrees: 0 }));
+        assert_eq!(result.condition, Some(WeatherCondition::Overcast));
+        assert!(!result.is_day);
     }
 
     #[test]
     fn deserialize_throws_error_on_api_error() {
         // The weather API returns an "error" JSON field if there was an error,
-        // so we use this to trigger the WeatherInfoNotFound error.
-        let result = deserialize(Ok(r#"{"error": true}"#.to_string()));
+        // so we use this to trigger the Api error.
+        let result = deserialize(
+            Ok(
+                r#"{"error": true, "reason": "Latitude must be in range of -90 to 90°."}"#
+                    .to_string(),
+            ),
+            UNITS.0,
+            UNITS.1,
+        );
 
         assert!(result.is_err());
 
         if let Some(err) = result.err() {
-            assert!(err.to_string().contains("Weather information not found"));
+            assert!(matches!(err, CustomError::Api(_)));
+            assert!(err.to_string().contains("Latitude must be in range"));
         }
     }
+
+    #[test]
+    fn deserialize_throws_error_on_missing_field() {
+        let json = r#"{"current_weather":{"time":1702740600,"temperature":8.8}}"#.to_string();
+
+        let result = deserialize(Ok(json), UNITS.0, UNITS.1);
+
+        assert!(matches!(result, Err(CustomError::MissingField(_))));
+    }
+
+    #[test]
+    fn deserialize_daily_creates_correct_daily_forecast_struct() {
+        let json = r#"{"utc_offset_seconds":3600,"daily":{"time":[1702681200,1702767600],"weathercode":[3,61],"temperature_2m_max":[9.1,7.4],"temperature_2m_min":[2.3,1.8],"precipitation_sum":[0.0,4.2],"windspeed_10m_max":[14.5,22.1],"sunrise":[1702711080,1702797510],"sunset":[1702739880,1702826310]}}"#.to_string();
+
+        let result = deserialize_daily(Ok(json), UNITS.0, UNITS.1).unwrap();
+
+        assert_eq!(
+            result.zone,
+            Zone::Fixed(FixedOffset::east_opt(3600).unwrap())
+        );
+        assert_eq!(result.days.len(), 2);
+        assert_eq!(result.days[1].timestamp.timestamp(), 1702767600);
+        assert_eq!(
+            result.days[1].condition,
+            Some(WeatherCondition::Rain(Intensity::Light))
+        );
+        assert_eq!(result.days[1].temperature_max, celsius(7.4));
+        assert_eq!(result.days[1].temperature_min, celsius(1.8));
+        assert_eq!(result.days[1].precipitation_sum, Some(4.2));
+        assert_eq!(result.days[1].windspeed_max, kmh(22.1));
+        assert_eq!(
+            result.days[1].sunrise,
+            DateTime::from_timestamp(1702797510, 0)
+        );
+        assert_eq!(
+            result.days[1].sunset,
+            DateTime::from_timestamp(1702826310, 0)
+        );
+    }
+
+    #[test]
+    fn deserialize_daily_reads_codes_sent_as_floats() {
+        let json = r#"{"daily":{"time":[1702681200,1702767600],"weathercode":[3.0,61.5],"temperature_2m_max":[9.1,7.4],"temperature_2m_min":[2.3,1.8],"precipitation_sum":[0.0,4.2],"windspeed_10m_max":[14.5,22.1],"sunrise":[1702711080,1702797510],"sunset":[1702739880,1702826310]}}"#.to_string();
+
+        let result = deserialize_daily(Ok(json), UNITS.0, UNITS.1).unwrap();
+
+        assert_eq!(result.days[0].condition, Some(WeatherCondition::Overcast));
+        assert_eq!(result.days[1].condition, None);
+    }
+
+    #[test]
+    fn deserialize_daily_throws_error_on_missing_series() {
+        let json = r#"{"daily":{"time":[1702681200],"weathercode":[3]}}"#.to_string();
+
+        let result = deserialize_daily(Ok(json), UNITS.0, UNITS.1);
+
+        assert!(result.is_err());
+
+        if let Some(err) = result.err() {
+            assert!(matches!(err, CustomError::MissingField(_)));
+            assert!(err
+                .to_string()
+                .contains("daily: missing field `temperature_2m_max`"));
+        }
+    }
+
+    #[test]
+    fn deserialize_hourly_creates_correct_hourly_forecast_struct() {
+        let json = r#"{"utc_offset_seconds":32400,"hourly":{"time":[1702738800,1702742400],"temperature_2m":[8.8,8.1],"precipitation_probability":[10,35],"windspeed_10m":[12.7,14.2],"winddirection_10m":[230,245],"cloudcover":[80,100]}}"#.to_string();
+
+        let result = deserialize_hourly(Ok(json), UNITS.0, UNITS.1).unwrap();
+
+        assert_eq!(
+            result.zone,
+            Zone::Fixed(FixedOffset::east_opt(32400).unwrap())
+        );
+        assert_eq!(result.hours.len(), 2);
+        assert_eq!(result.hours[1].timestamp.timestamp(), 1702742400);
+        assert_eq!(result.hours[1].temperature, celsius(8.1));
+        assert_eq!(result.hours[1].precipitation_probability, Some(35));
+        assert_eq!(result.hours[1].windspeed, kmh(14.2));
+        assert_eq!(
+            result.hours[1].winddirection,
+            Some(WindDirection { degrees: 245 })
+        );
+        assert_eq!(result.hours[1].cloudcover, Some(100));
+    }
+
+    #[test]
+    fn deserialize_hourly_reads_whole_numbers_sent_as_floats() {
+        let json = r#"{"hourly":{"time":[1702738800],"temperature_2m":[8.8],"precipitation_probability":[34.6],"windspeed_10m":[12.7],"winddirection_10m":[230.0],"cloudcover":[100.0]}}"#.to_string();
+
+        let result = deserialize_hourly(Ok(json), UNITS.0, UNITS.1).unwrap();
+
+        assert_eq!(result.hours[0].precipitation_probability, Some(35));
+        assert_eq!(
+            result.hours[0].winddirection,
+            Some(WindDirection { degrees: 230 })
+        );
+        assert_eq!(result.hours[0].cloudcover, Some(100));
+    }
+
+    #[test]
+    fn deserialize_hourly_keeps_missing_values_as_none() {
+        let json = r#"{"hourly":{"time":[1702738800,1702742400],"temperature_2m":[8.8,null],"precipitation_probability":[null,35],"windspeed_10m":[12.7],"winddirection_10m":[230,245],"cloudcover":[80,100]}}"#.to_string();
+
+        let result = deserialize_hourly(Ok(json), UNITS.0, UNITS.1).unwrap();
+
+        assert_eq!(result.hours[0].precipitation_probability, None);
+        assert_eq!(result.hours[1].temperature, None);
+        assert_eq!(result.hours[1].windspeed, None);
+        assert_eq!(result.hours[1].cloudcover, Some(100));
+    }
+
+    #[test]
+    fn deserialize_throws_error_on_wrongly_typed_field() {
+        let json = r#"{"current_weather":{"time":1702740600,"temperature":"warm","windspeed":12.7,"is_day":1}}"#.to_string();
+
+        let result = deserialize(Ok(json), UNITS.0, UNITS.1);
+
+        assert!(matches!(result, Err(CustomError::MissingField(_))));
+    }
 }
@@ -0,0 +1,665 @@
+use httpmock::prelude::*;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Runs the binary isolated from the user's configuration, cache, data and environment.
+/// The bundled city table of the `gazetteer` feature is turned off, so cities are geocoded
+/// by the mocked API, unless `envs` turns it on again.
+fn run(args: &[&str], envs: &[(&str, &str)]) -> Output {
+    run_in(tempfile::tempdir().unwrap().path(), args, envs)
+}
+
+/// Runs the binary like `run`, keeping its configuration, cache and data in `home`.
+fn run_in(home: &Path, args: &[&str], envs: &[(&str, &str)]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_weather-wand"));
+    for (name, _) in std::env::vars().filter(|(name, _)| name.starts_with("WEATHER_WAND_")) {
+        command.env_remove(name);
+    }
+
+    command
+        .args(args)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_DATA_HOME", home.join("data"))
+        .env("WEATHER_WAND_GAZETTEER", "off")
+        .envs(envs.iter().copied())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn shows_weather_from_custom_api_endpoints() {
+    let server = MockServer::start();
+    let geo_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/search")
+            .query_param("name", "Berlin");
+        then.status(200).body(
+            r#"{"results":[{"name":"Berlin","latitude":52.52437,"longitude":13.41053,"timezone":"Europe/Berlin","country_code":"DE","admin1":"Land Berlin","country":"Germany"}]}"#,
+        );
+    });
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "52.52437")
+            .query_param("longitude", "13.41053");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":3600,"current_weather":{"time":1702740600,"temperature":4.2,"windspeed":11.3,"is_day":0}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "Berlin",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[],
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("Berlin"));
+    assert!(stdout.contains("4.2"));
+    geo_mock.assert();
+    weather_mock.assert();
+}
+
+#[test]
+fn shows_weather_for_place_without_region_or_timezone() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/search");
+        then.status(200).body(
+            r#"{"results":[{"name":"McMurdo Station","latitude":-77.846,"longitude":166.676,"elevation":24.0,"feature_code":"STNB","country_code":"AQ","country":"Antarctica"}]}"#,
+        );
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/forecast");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":46800,"timezone":"Antarctica/McMurdo","current_weather":{"time":1702740600,"temperature":-3.1,"windspeed":20.5,"is_day":1}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "McMurdo Station",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[],
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("Antarctica"));
+    assert!(!stdout.contains(", Antarctica"));
+    assert!(stdout.contains("Antarctica/McMurdo"));
+    assert!(stdout.contains("24 m"));
+}
+
+#[test]
+fn shows_place_names_and_labels_in_chosen_language() {
+    let server = MockServer::start();
+    let geo_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/search")
+            .query_param("name", "Tokyo")
+            .query_param("language", "ja");
+        then.status(200).body(
+            r#"{"results":[{"name":"東京","latitude":35.6895,"longitude":139.69171,"timezone":"Asia/Tokyo","country_code":"JP","admin1":"東京都","country":"日本"}]}"#,
+        );
+    });
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/forecast");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":32400,"current_weather":{"time":1702740600,"temperature":9.8,"windspeed":5.1,"is_day":1},
+                "daily":{"time":[1702652400],"temperature_2m_min":[3.1],"temperature_2m_max":[12.4],"precipitation_sum":[0.0],"windspeed_10m_max":[8.2],"weathercode":[1],"sunrise":[1702675620],"sunset":[1702711380]}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "Tokyo",
+            "--lang",
+            "ja",
+            "--days",
+            "1",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[],
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("東京, JPの現在の天気"));
+    assert!(stdout.contains("気温"));
+    assert!(stdout.contains("東京都, 日本"));
+    assert!(stdout.contains("12月16日(土)"));
+    geo_mock.assert();
+    weather_mock.assert_hits(2);
+}
+
+#[test]
+fn rejects_api_url_with_leftover_placeholder() {
+    let output = run(
+        &["--city", "Berlin"],
+        &[(
+            "WEATHER_WAND_WEATHER_API_URL",
+            "http://localhost/v1/forecast?lat=__LAT__&lon=__LON__",
+        )],
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr.contains("__LAT__"));
+}
+
+const FRANKFURTS: &str = r#"{"results":[
+    {"name":"Frankfurt (Oder)","latitude":52.34714,"longitude":14.55062,"timezone":"Europe/Berlin","country_code":"DE","admin1":"Brandenburg","country":"Germany","population":57751},
+    {"name":"Frankfurt am Main","latitude":50.11552,"longitude":8.68417,"timezone":"Europe/Berlin","country_code":"DE","admin1":"Hesse","country":"Germany","population":650000}
+]}"#;
+
+#[test]
+fn lists_candidates_for_ambiguous_city_when_not_interactive() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/search");
+        then.status(200).body(FRANKFURTS);
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "Frankfurt",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+        ],
+        &[],
+    );
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(12));
+    assert!(stderr.contains("1) Frankfurt (Oder), Brandenburg, Germany"));
+    assert!(stderr.contains("2) Frankfurt am Main, Hesse, Germany"));
+}
+
+#[test]
+fn picks_candidate_of_ambiguous_city() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/search");
+        then.status(200).body(FRANKFURTS);
+    });
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "50.11552");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":3600,"current_weather":{"time":1702740600,"temperature":6.1,"windspeed":9.4,"is_day":0}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "Frankfurt",
+            "--pick",
+            "2",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Frankfurt am Main"));
+    weather_mock.assert();
+}
+
+#[test]
+fn narrows_qualified_city_to_matching_place() {
+    let server = MockServer::start();
+    let geo_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/search")
+            .query_param("name", "Portland")
+            .query_param("count", "50")
+            .query_param("countryCode", "US");
+        then.status(200).body(
+            r#"{"results":[
+                {"name":"Portland","latitude":45.52345,"longitude":-122.67621,"timezone":"America/Los_Angeles","country_code":"US","admin1":"Oregon","country":"United States"},
+                {"name":"Portland","latitude":43.66147,"longitude":-70.25533,"timezone":"America/New_York","country_code":"US","admin1":"Maine","country":"United States"}
+            ]}"#,
+        );
+    });
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "43.66147");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":-18000,"current_weather":{"time":1702740600,"temperature":-1.5,"windspeed":20.3,"is_day":1}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "Portland, ME",
+            "--country",
+            "us",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    geo_mock.assert();
+    weather_mock.assert();
+}
+
+#[test]
+fn saved_locations_are_used_instead_of_geocoding() {
+    let home = tempfile::tempdir().unwrap();
+    let server = MockServer::start();
+    let geo_mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/search");
+        then.status(200).body(FRANKFURTS);
+    });
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "50.11552");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":3600,"current_weather":{"time":1702740600,"temperature":6.1,"windspeed":9.4,"is_day":0}}"#,
+        );
+    });
+    let geo_api_url = server.url("/v1/search");
+    let weather_api_url = server.url("/v1/forecast");
+    let envs = [
+        ("WEATHER_WAND_GEO_API_BASE_URL", geo_api_url.as_str()),
+        (
+            "WEATHER_WAND_WEATHER_API_BASE_URL",
+            weather_api_url.as_str(),
+        ),
+    ];
+
+    let output = run_in(
+        home.path(),
+        &["locations", "add", "Office", "Frankfurt", "--pick", "2"],
+        &envs,
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Frankfurt am Main, DE"));
+
+    let output = run_in(home.path(), &["--city", "office", "--no-cache"], &envs);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Frankfurt am Main"));
+
+    let output = run_in(
+        home.path(),
+        &["locations", "rename", "office", "default"],
+        &envs,
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = run_in(home.path(), &["--no-cache"], &envs);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = run_in(home.path(), &["locations", "list"], &envs);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("default  Frankfurt am Main, DE"));
+
+    let output = run_in(home.path(), &["locations", "remove", "office"], &envs);
+    assert_eq!(output.status.code(), Some(13));
+
+    geo_mock.assert_hits(1);
+    weather_mock.assert_hits(2);
+}
+
+#[test]
+fn shows_weather_for_coordinates_without_geocoding() {
+    let server = MockServer::start();
+    let geo_mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/search");
+        then.status(500);
+    });
+    let reverse_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/reverse")
+            .query_param("lat", "-33.87")
+            .query_param("lon", "151.21");
+        then.status(200).body(
+            r#"{"lat":"-33.8698","lon":"151.2083","address":{"city":"Sydney","state":"New South Wales","country":"Australia","country_code":"au"}}"#,
+        );
+    });
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "-33.87")
+            .query_param("longitude", "151.21");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":39600,"timezone":"Australia/Sydney","current_weather":{"time":1702740600,"temperature":24.5,"windspeed":15.1,"is_day":1}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--coords",
+            "-33.87,151.21",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--reverse-geo-api-url",
+            &server.url("/reverse"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[],
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("Sydney, AU"));
+    assert!(stdout.contains("New South Wales, Australia"));
+    assert!(stdout.contains("-33.87, 151.21"));
+    assert!(stdout.contains("Australia/Sydney"));
+    geo_mock.assert_hits(0);
+    reverse_mock.assert();
+    weather_mock.assert();
+}
+
+#[test]
+fn names_coordinates_after_themselves_without_reverse_geocoder() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/forecast");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":0,"timezone":"GMT","current_weather":{"time":1702740600,"temperature":18.0,"windspeed":30.2,"is_day":1}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--lat",
+            "0.5",
+            "--lon",
+            "-20.25",
+            "--no-cache",
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[("WEATHER_WAND_REVERSE_GEOCODER", "none")],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0.5, -20.25"));
+}
+
+#[test]
+fn marks_report_and_forecasts_served_offline() {
+    let home = tempfile::tempdir().unwrap();
+    let server = MockServer::start();
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/forecast");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":0,"timezone":"GMT","current":{"time":1702740600,"temperature_2m":4.2,"windspeed_10m":11.3,"is_day":0},
+                "daily":{"time":[1702684800],"temperature_2m_min":[3.1],"temperature_2m_max":[12.4],"precipitation_sum":[0.0],"windspeed_10m_max":[8.2],"weathercode":[1],"sunrise":[1702713600],"sunset":[1702742400]},
+                "hourly":{"time":[1702742400],"temperature_2m":[4.0],"precipitation_probability":[10],"windspeed_10m":[11.0],"winddirection_10m":[230],"cloudcover":[80]}}"#,
+        );
+    });
+    let weather_api_url = server.url("/v1/forecast");
+    let args = [
+        "--coords",
+        "0.5,-20.25",
+        "--days",
+        "1",
+        "--hourly",
+        "1",
+        "--weather-api-url",
+        &weather_api_url,
+    ];
+    let envs = [("WEATHER_WAND_REVERSE_GEOCODER", "none")];
+
+    let output = run_in(home.path(), &args, &envs);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Offline"));
+
+    let output = run_in(home.path(), &[&args[..], &["--offline"]].concat(), &envs);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(stdout.matches("Offline: stored data").count(), 3);
+    weather_mock.assert_hits(3);
+}
+
+#[test]
+fn sends_configured_and_flag_headers() {
+    let server = MockServer::start();
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .header("x-team", "field-ops")
+            .header("x-api-key", "abc,123")
+            .header("x-debug", "1");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":0,"timezone":"GMT","current":{"time":1702740600,"temperature_2m":4.2,"windspeed_10m":11.3,"is_day":0}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--coords",
+            "0.5,-20.25",
+            "--header",
+            "X-Debug: 1",
+            "--no-cache",
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[
+            ("WEATHER_WAND_REVERSE_GEOCODER", "none"),
+            (
+                "WEATHER_WAND_HTTP_HEADERS",
+                "X-Team: field-ops\nX-Api-Key: abc,123",
+            ),
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    weather_mock.assert();
+}
+
+#[test]
+fn shows_only_selected_fields() {
+    let home = tempfile::tempdir().unwrap();
+    let server = MockServer::start();
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param(
+                "current",
+                "temperature_2m,windspeed_10m,winddirection_10m,weathercode,is_day,apparent_temperature,relativehumidity_2m,surface_pressure,precipitation,cloudcover,visibility,windgusts_10m,uv_index",
+            );
+        then.status(200).body(
+            r#"{"utc_offset_seconds":3600,"timezone":"Europe/Berlin","current":{"time":1702740600,"temperature_2m":4.2,"windspeed_10m":11.3,"winddirection_10m":230,"weathercode":3,"is_day":0,"surface_pressure":1008.7,"relativehumidity_2m":82}}"#,
+        );
+    });
+    let weather_api_url = server.url("/v1/forecast");
+    let envs = [("WEATHER_WAND_REVERSE_GEOCODER", "none")];
+
+    let output = run_in(
+        home.path(),
+        &[
+            "--coords",
+            "52.52,13.41",
+            "--fields",
+            "pressure,humidity",
+            "--weather-api-url",
+            &weather_api_url,
+        ],
+        &envs,
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("1008.7 hPa"));
+    assert!(stdout.contains("82%"));
+    assert!(stdout.find("Pressure") < stdout.find("Humidity"));
+    assert!(!stdout.contains("Temperature"));
+    assert!(!stdout.contains("Europe/Berlin"));
+
+    // Other fields are served from the same cached response.
+    let output = run_in(
+        home.path(),
+        &[
+            "--coords",
+            "52.52,13.41",
+            "--fields",
+            "temperature,timezone",
+            "--offline",
+            "--weather-api-url",
+            &weather_api_url,
+        ],
+        &envs,
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("Temperature"));
+    assert!(stdout.contains("Europe/Berlin"));
+    assert!(!stdout.contains("Pressure"));
+    weather_mock.assert();
+}
+
+#[cfg(feature = "gazetteer")]
+#[test]
+fn resolves_city_from_gazetteer_without_geocoding() {
+    let server = MockServer::start();
+    let geo_mock = server.mock(|when, then| {
+        when.method(GET).path("/v1/search");
+        then.status(500);
+    });
+    let weather_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "48.13743");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":3600,"current_weather":{"time":1702740600,"temperature":2.8,"windspeed":7.6,"is_day":0}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "münchen",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[("WEATHER_WAND_GAZETTEER", "on")],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Munich, DE"));
+    geo_mock.assert_hits(0);
+    weather_mock.assert();
+}
+
+#[cfg(feature = "gazetteer")]
+#[test]
+fn geocodes_names_missing_from_gazetteer() {
+    let server = MockServer::start();
+    let geo_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/search")
+            .query_param("name", "Kiel");
+        then.status(200).body(
+            r#"{"results":[{"name":"Kiel","latitude":54.32133,"longitude":10.13489,"timezone":"Europe/Berlin","country_code":"DE","admin1":"Schleswig-Holstein","country":"Germany"}]}"#,
+        );
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "54.32133");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":3600,"current_weather":{"time":1702740600,"temperature":3.4,"windspeed":25.1,"is_day":0}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "Kiel",
+            "--no-cache",
+            "--geo-api-url",
+            &server.url("/v1/search"),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[("WEATHER_WAND_GAZETTEER", "on")],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Kiel, DE"));
+    geo_mock.assert();
+}
+
+#[cfg(feature = "gazetteer")]
+#[test]
+fn guesses_misspelled_city_from_gazetteer_when_api_is_unreachable() {
+    // Bind to a free port and release it again, so nothing is listening there.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/forecast")
+            .query_param("latitude", "48.13743");
+        then.status(200).body(
+            r#"{"utc_offset_seconds":3600,"current_weather":{"time":1702740600,"temperature":2.8,"windspeed":7.6,"is_day":0}}"#,
+        );
+    });
+
+    let output = run(
+        &[
+            "--city",
+            "Muenchn",
+            "--no-cache",
+            "--retries",
+            "0",
+            "--geo-api-url",
+            &format!("http://127.0.0.1:{}/v1/search", port),
+            "--weather-api-url",
+            &server.url("/v1/forecast"),
+        ],
+        &[("WEATHER_WAND_GAZETTEER", "on")],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Munich, DE"));
+}